@@ -1,8 +1,10 @@
+use crate::Money;
+
 #[derive(PartialEq, Copy, Clone, Debug)]
 pub enum Action {
     Hit,
     Stand,
-    Double,
+    Double(Money),
     Split,
     Surrender,
 }
@@ -10,10 +12,35 @@ pub enum Action {
 #[derive(Debug)]
 pub struct PossibleAction(pub(crate) Action);
 
+/// What the active hand of a round is waiting on before play can continue.
+#[derive(Debug)]
+pub enum Decision {
+    EarlySurrender,
+    /// The most insurance the hand can take.
+    Insurance(Money),
+    Action(Vec<PossibleAction>),
+}
+
+#[derive(Debug)]
+pub enum Response {
+    EarlySurrender(bool),
+    Insurance(Money),
+    Action(PossibleAction),
+}
+
 impl PossibleAction {
     pub fn action(&self) -> Action {
         self.0
     }
+
+    pub fn double_for(&self, amount: Money) -> Option<PossibleAction> {
+        match self.0 {
+            Action::Double(max) if amount > Money::ZERO && amount <= max => {
+                Some(PossibleAction(Action::Double(amount)))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl PartialEq for PossibleAction {
@@ -60,4 +87,19 @@ mod action_tests {
         let possible_action = &&&PossibleAction(Action::Hit);
         assert!(possible_action == Action::Hit);
     }
+
+    #[test]
+    fn possible_action_double_for_less() {
+        let possible_action = PossibleAction(Action::Double(Money::from_dollars(10)));
+        assert_eq!(
+            possible_action.double_for(Money::from_dollars(5)),
+            Some(PossibleAction(Action::Double(Money::from_dollars(5))))
+        );
+        assert_eq!(possible_action.double_for(Money::from_dollars(15)), None);
+        assert_eq!(possible_action.double_for(Money::ZERO), None);
+        assert_eq!(
+            PossibleAction(Action::Hit).double_for(Money::from_dollars(5)),
+            None
+        );
+    }
 }
@@ -0,0 +1,193 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+impl Suit {
+    pub const ALL: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+pub enum Rank {
+    Ace = 1,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+}
+
+impl Rank {
+    pub const ALL: [Rank; 13] = [
+        Rank::Ace,
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+    ];
+
+    pub fn value(&self) -> u8 {
+        (*self as u8).min(10)
+    }
+}
+
+impl TryFrom<u8> for Rank {
+    type Error = ParseCardError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1..=13 => Ok(Rank::ALL[value as usize - 1]),
+            _ => Err(ParseCardError::InvalidRank),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct Card {
+    pub rank: Rank,
+    pub suit: Suit,
+}
+
+impl Card {
+    pub fn new(rank: Rank, suit: Suit) -> Card {
+        Card { rank, suit }
+    }
+
+    pub fn value(&self) -> u8 {
+        self.rank.value()
+    }
+
+    pub fn is_ace(&self) -> bool {
+        self.rank == Rank::Ace
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub enum ParseCardError {
+    InvalidRank,
+    InvalidSuit,
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rank::Ace => write!(f, "A"),
+            Rank::Jack => write!(f, "J"),
+            Rank::Queen => write!(f, "Q"),
+            Rank::King => write!(f, "K"),
+            x => write!(f, "{}", *x as u8),
+        }
+    }
+}
+
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Suit::Clubs => write!(f, "♣"),
+            Suit::Diamonds => write!(f, "♦"),
+            Suit::Hearts => write!(f, "♥"),
+            Suit::Spades => write!(f, "♠"),
+        }
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.rank, self.suit)
+    }
+}
+
+impl FromStr for Rank {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "A" => Ok(Rank::Ace),
+            "T" => Ok(Rank::Ten),
+            "J" => Ok(Rank::Jack),
+            "Q" => Ok(Rank::Queen),
+            "K" => Ok(Rank::King),
+            x => match x.parse::<u8>() {
+                Ok(value @ 2..=10) => Rank::try_from(value),
+                _ => Err(ParseCardError::InvalidRank),
+            },
+        }
+    }
+}
+
+impl FromStr for Suit {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "C" | "c" | "♣" => Ok(Suit::Clubs),
+            "D" | "d" | "♦" => Ok(Suit::Diamonds),
+            "H" | "h" | "♥" => Ok(Suit::Hearts),
+            "S" | "s" | "♠" => Ok(Suit::Spades),
+            _ => Err(ParseCardError::InvalidSuit),
+        }
+    }
+}
+
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.char_indices().last() {
+            None => Err(ParseCardError::InvalidRank),
+            Some((i, _)) => Ok(Card::new(s[..i].parse()?, s[i..].parse()?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod card_tests {
+    use super::*;
+
+    #[test]
+    fn card_value() {
+        assert_eq!(Card::new(Rank::Ace, Suit::Spades).value(), 1);
+        assert_eq!(Card::new(Rank::Seven, Suit::Hearts).value(), 7);
+        assert_eq!(Card::new(Rank::Ten, Suit::Clubs).value(), 10);
+        assert_eq!(Card::new(Rank::King, Suit::Diamonds).value(), 10);
+    }
+
+    #[test]
+    fn card_display() {
+        assert_eq!(Card::new(Rank::King, Suit::Spades).to_string(), "K♠");
+        assert_eq!(Card::new(Rank::Ten, Suit::Hearts).to_string(), "10♥");
+    }
+
+    #[test]
+    fn card_parse() {
+        assert_eq!("AS".parse(), Ok(Card::new(Rank::Ace, Suit::Spades)));
+        assert_eq!("10h".parse(), Ok(Card::new(Rank::Ten, Suit::Hearts)));
+        assert_eq!("qd".parse(), Ok(Card::new(Rank::Queen, Suit::Diamonds)));
+        assert_eq!("7♣".parse(), Ok(Card::new(Rank::Seven, Suit::Clubs)));
+        assert_eq!("1S".parse::<Card>(), Err(ParseCardError::InvalidRank));
+        assert_eq!("AX".parse::<Card>(), Err(ParseCardError::InvalidSuit));
+        assert_eq!("".parse::<Card>(), Err(ParseCardError::InvalidRank));
+    }
+}
@@ -1,227 +1,1186 @@
 use crate::game::PlayerBalanceError::{
-    BalanceCannotBeNegative, HandDoesNotExist, PlayerDoesNotExist,
+    BalanceCannotBeNegative, PlayerDoesNotExist, PlayerInRound, PlayerMaximumExceeded,
+    SeatDoesNotExist, SeatNotTaken, SeatOccupied,
 };
 use crate::hand::Hand;
 use crate::player_hand::PlayerHand;
 use crate::round::Round;
-use crate::shoe::Shoe;
-use crate::HandStatus::{Stood, Value};
-use crate::PossibleAction;
-use std::cell::RefCell;
+use crate::seat::{Account, PlayerId, Seat};
+use crate::shoe::{Shoe, ShoeError};
+use crate::HandStatus::{Blackjack, Stood, Value};
+use crate::{
+    Action, Decision, HoleCardRule, Ledger, Money, Player, PossibleAction, Reason, Response,
+    TableRules,
+};
+use std::cell::{Ref, RefCell};
+use std::ops::ControlFlow;
 use std::rc::Rc;
 
-#[derive(Debug)]
+#[derive(PartialEq, Debug)]
 pub enum PlayerBalanceError {
     PlayerDoesNotExist,
-    HandDoesNotExist,
     BalanceCannotBeNegative,
+    BetBelowMinimum,
+    BetAboveMaximum,
+    BetNotMultipleOfIncrement,
+    PlayerMaximumExceeded,
+    SeatDoesNotExist,
+    SeatOccupied,
+    SeatNotTaken,
+    PlayerInRound,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum GameError {
+    RoundInProgress,
+    NoRoundInProgress,
+    DecisionPending,
+    InvalidResponse,
+    NoBets,
+    Shoe(ShoeError),
+    Balance(PlayerBalanceError),
+}
+
+impl From<ShoeError> for GameError {
+    fn from(error: ShoeError) -> Self {
+        GameError::Shoe(error)
+    }
+}
+
+impl From<PlayerBalanceError> for GameError {
+    fn from(error: PlayerBalanceError) -> Self {
+        GameError::Balance(error)
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct SessionSummary {
+    /// The name and balance of every player still at the table.
+    pub balances: Vec<(String, Money)>,
+    pub rounds_played: usize,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+enum Stage {
+    Betting,
+    EarlySurrender(usize),
+    Insurance(usize),
+    Playing,
+    DealerTurn,
 }
 
 pub struct Game {
     shoe: Box<dyn Shoe>,
-    get_action: fn(&Round, Vec<PossibleAction>) -> PossibleAction,
-    player_balances: Vec<Rc<RefCell<f64>>>,
-    player_bets: Vec<Vec<f64>>,
+    rules: TableRules,
+    players: Vec<Option<Account>>,
+    seats: Vec<Option<Seat>>,
+    ledger: Rc<RefCell<Ledger>>,
+    round: Round,
+    stage: Stage,
+    rounds_played: usize,
 }
 
 impl Game {
+    pub fn new(shoe: Box<dyn Shoe>, rules: TableRules) -> Game {
+        Game {
+            shoe,
+            rules,
+            players: vec![],
+            seats: vec![None; rules.seats],
+            ledger: Rc::new(RefCell::new(Ledger::new())),
+            round: empty_round(),
+            stage: Stage::Betting,
+            rounds_played: 0,
+        }
+    }
+
+    /// Seats each player at the next seat and plays rounds until every player has stopped.
     pub fn start_game(
         shoe: Box<dyn Shoe>,
-        new_round: fn(&mut Game, &Round),
-        get_action: fn(&Round, Vec<PossibleAction>) -> PossibleAction,
-    ) {
-        let mut game = Game {
-            shoe,
-            get_action,
-            player_balances: vec![],
-            player_bets: vec![],
-        };
+        rules: TableRules,
+        players: Vec<(&str, Money, Box<dyn Player>)>,
+    ) -> Result<SessionSummary, GameError> {
+        let mut game = Game::new(shoe, rules);
+        for (seat, (name, balance, player)) in players.into_iter().enumerate() {
+            let id = game.join(name, balance, Some(player))?;
+            game.take_seat(id, seat)?;
+        }
 
-        let mut round = Round {
-            player_hands: vec![],
-            active_hand_index: 0,
-            dealer: Hand {
-                cards: vec![],
-                status: Value,
-                value: 0,
-                soft: false,
-            },
-        };
+        while game.play_round()?.is_continue() {}
+        Ok(game.summary())
+    }
 
-        loop {
-            game.reset_bets();
-            new_round(&mut game, &round);
-            round = game.play_round();
-            game.shoe.new_round()
+    pub fn summary(&self) -> SessionSummary {
+        SessionSummary {
+            balances: self
+                .players
+                .iter()
+                .flatten()
+                .map(|x| (x.name.clone(), *x.balance.borrow()))
+                .collect(),
+            rounds_played: self.rounds_played,
         }
     }
 
-    pub fn get_player_balances(&self) -> Vec<f64> {
-        self.player_balances.iter().map(|x| *x.borrow()).collect()
+    pub fn rounds_played(&self) -> usize {
+        self.rounds_played
     }
 
-    pub fn set_player_balances(
+    /// Adds a player to the table. They can't play until they take a seat.
+    pub fn join(
         &mut self,
-        player_balances: Vec<f64>,
-    ) -> Result<(), PlayerBalanceError> {
-        if player_balances.iter().any(|x| *x < 0.0) {
-            Err(BalanceCannotBeNegative)
-        } else {
-            self.player_balances = player_balances
-                .iter()
-                .map(|x| Rc::from(RefCell::from(*x)))
-                .collect();
-            self.reset_bets();
-            Ok(())
+        name: &str,
+        balance: Money,
+        player: Option<Box<dyn Player>>,
+    ) -> Result<PlayerId, PlayerBalanceError> {
+        if balance < Money::ZERO {
+            return Err(BalanceCannotBeNegative);
         }
+
+        let id = PlayerId(self.players.len());
+        self.players.push(Some(Account {
+            name: name.to_owned(),
+            balance: Rc::new(RefCell::new(balance)),
+            player,
+            sitting_out: false,
+        }));
+        self.ledger
+            .borrow_mut()
+            .record(id, None, None, Reason::Adjustment, balance);
+        Ok(id)
     }
 
-    pub fn get_player_balance(&self, player_index: usize) -> Result<f64, PlayerBalanceError> {
-        match self.player_balances.get(player_index) {
-            None => Err(PlayerDoesNotExist),
-            Some(x) => Ok(*x.borrow()),
+    /// Removes a player and their seats from the table, returning their final balance.
+    pub fn leave(&mut self, id: PlayerId) -> Result<Money, PlayerBalanceError> {
+        self.account(id)?;
+        if self.stage != Stage::Betting
+            && self
+                .round
+                .player_hands
+                .iter()
+                .any(|player_hand| player_hand.player == id)
+        {
+            return Err(PlayerInRound);
         }
-    }
 
-    pub fn set_player_balance(
-        &mut self,
-        player_index: usize,
-        new_balance: f64,
-    ) -> Result<(), PlayerBalanceError> {
-        match self.player_balances.get(player_index) {
-            None => Err(PlayerDoesNotExist),
-            Some(x) => {
-                if new_balance < 0.0 {
-                    Err(BalanceCannotBeNegative)
-                } else {
-                    *x.borrow_mut() = new_balance;
-                    self.reset_bet(player_index);
-                    Ok(())
-                }
+        for seat in &mut self.seats {
+            if seat.is_some_and(|x| x.player == id) {
+                *seat = None;
             }
         }
+        // the account was checked above
+        let account = self.players[id.0].take().unwrap();
+        let balance = *account.balance.borrow();
+        self.ledger
+            .borrow_mut()
+            .record(id, None, None, Reason::Adjustment, -balance);
+        Ok(balance)
     }
 
-    pub fn modify_player_balance(
-        &mut self,
-        player_index: usize,
-        new_balance: f64,
-    ) -> Result<(), PlayerBalanceError> {
-        match self.player_balances.get(player_index) {
-            None => Err(PlayerDoesNotExist),
+    pub fn take_seat(&mut self, id: PlayerId, seat: usize) -> Result<(), PlayerBalanceError> {
+        self.account(id)?;
+        match self.seats.get_mut(seat) {
+            None => Err(SeatDoesNotExist),
+            Some(Some(_)) => Err(SeatOccupied),
             Some(x) => {
-                if -new_balance > *x.borrow() {
-                    Err(BalanceCannotBeNegative)
-                } else {
-                    *x.borrow_mut() += new_balance;
-                    self.reset_bet(player_index);
-                    Ok(())
-                }
+                *x = Some(Seat {
+                    player: id,
+                    bet: Money::ZERO,
+                });
+                Ok(())
             }
         }
     }
 
-    pub fn reset_bets(&mut self) {
-        for i in 0..self.player_balances.len() {
-            self.reset_bet(i)
+    pub fn leave_seat(&mut self, id: PlayerId, seat: usize) -> Result<(), PlayerBalanceError> {
+        self.seat(id, seat)?;
+        if self.stage != Stage::Betting
+            && self
+                .round
+                .player_hands
+                .iter()
+                .any(|player_hand| player_hand.seat == seat)
+        {
+            return Err(PlayerInRound);
         }
+        self.seats[seat] = None;
+        Ok(())
     }
 
-    fn reset_bet(&mut self, index: usize) {
-        match self.player_bets.get(index) {
-            None => self.player_bets.insert(index, vec![0.0]),
-            Some(x) => {
-                if x.iter().sum::<f64>() > *self.player_balances[index].borrow() {
-                    self.player_bets[index] = vec![0.0];
+    /// A player sitting out keeps their seats but isn't dealt in until they come back.
+    pub fn sit_out(&mut self, id: PlayerId, sitting_out: bool) -> Result<(), PlayerBalanceError> {
+        self.account_mut(id)?.sitting_out = sitting_out;
+        Ok(())
+    }
+
+    pub fn is_sitting_out(&self, id: PlayerId) -> Result<bool, PlayerBalanceError> {
+        Ok(self.account(id)?.sitting_out)
+    }
+
+    pub fn player_at(&self, seat: usize) -> Result<Option<PlayerId>, PlayerBalanceError> {
+        match self.seats.get(seat) {
+            None => Err(SeatDoesNotExist),
+            Some(x) => Ok(x.map(|x| x.player)),
+        }
+    }
+
+    pub fn seats_of(&self, id: PlayerId) -> Vec<usize> {
+        (0..self.seats.len())
+            .filter(|&x| self.seats[x].is_some_and(|x| x.player == id))
+            .collect()
+    }
+
+    pub fn name(&self, id: PlayerId) -> Result<&str, PlayerBalanceError> {
+        Ok(&self.account(id)?.name)
+    }
+
+    fn account(&self, id: PlayerId) -> Result<&Account, PlayerBalanceError> {
+        match self.players.get(id.0) {
+            Some(Some(x)) => Ok(x),
+            _ => Err(PlayerDoesNotExist),
+        }
+    }
+
+    fn account_mut(&mut self, id: PlayerId) -> Result<&mut Account, PlayerBalanceError> {
+        match self.players.get_mut(id.0) {
+            Some(Some(x)) => Ok(x),
+            _ => Err(PlayerDoesNotExist),
+        }
+    }
+
+    fn seat(&self, id: PlayerId, seat: usize) -> Result<Seat, PlayerBalanceError> {
+        self.account(id)?;
+        match self.seats.get(seat) {
+            None => Err(SeatDoesNotExist),
+            Some(Some(x)) if x.player == id => Ok(*x),
+            Some(_) => Err(SeatNotTaken),
+        }
+    }
+
+    /// Collects a bet for every seat whose player has a `Player` and plays a whole round, asking
+    /// the `Player` for each hand's decisions. A bet of zero leaves the seat out of the round, as do
+    /// seats whose player has no `Player`, and nothing is dealt if nobody bet. A player who stops
+    /// instead of betting, or bets what the table or their balance won't allow, sits out, and no
+    /// round is played once nobody is left playing.
+    pub fn play_round(&mut self) -> Result<ControlFlow<(), &Round>, GameError> {
+        for seat in 0..self.seats.len() {
+            let id = match self.seats[seat] {
+                None => continue,
+                Some(x) => x.player,
+            };
+            let account = self.account_mut(id)?;
+            if account.sitting_out {
+                continue;
+            }
+            let mut player = match account.player.take() {
+                None => continue,
+                Some(x) => x,
+            };
+            let bet = player.place_bet(self, seat);
+            self.account_mut(id)?.player = Some(player);
+
+            match bet {
+                ControlFlow::Continue(x) if x == Money::ZERO => self.clear_bet(seat)?,
+                ControlFlow::Continue(x) => {
+                    if self.set_bet(seat, x).is_err() {
+                        self.clear_bet(seat)?;
+                        self.account_mut(id)?.sitting_out = true;
+                    }
                 }
+                ControlFlow::Break(()) => self.account_mut(id)?.sitting_out = true,
             }
         }
+
+        let playing = self.seats.iter().flatten().any(|seat| {
+            matches!(&self.players[seat.player.0], Some(x) if !x.sitting_out && x.player.is_some())
+        });
+        if !playing {
+            return Ok(ControlFlow::Break(()));
+        }
+        if self.create_player_hands(true)?.is_empty() {
+            return Ok(ControlFlow::Continue(&self.round));
+        }
+
+        self.deal_round(true)?;
+        while let Some(decision) = self.pending_decision() {
+            let id = self.round.player_hands[self.round.active_hand_index].player;
+            let player = match self.players[id.0].as_mut().and_then(|x| x.player.as_mut()) {
+                None => return Err(GameError::Balance(PlayerDoesNotExist)),
+                Some(x) => x,
+            };
+            let response = match decision {
+                Decision::EarlySurrender => {
+                    Response::EarlySurrender(player.early_surrender(&self.round))
+                }
+                Decision::Insurance(max) => {
+                    Response::Insurance(player.insurance(&self.round, max).clamp(Money::ZERO, max))
+                }
+                Decision::Action(possible) => {
+                    Response::Action(player.decide(&self.round, possible))
+                }
+            };
+            self.apply(response)?;
+        }
+        Ok(ControlFlow::Continue(self.finish_round()?))
     }
 
-    pub fn get_bet(
-        &self,
-        player_index: usize,
-        hand_index: usize,
-    ) -> Result<f64, PlayerBalanceError> {
-        match self.player_balances.get(player_index) {
-            None => Err(PlayerDoesNotExist),
-            Some(_) => match self.player_bets[player_index].get(hand_index) {
-                None => Err(HandDoesNotExist),
-                Some(x) => Ok(*x),
-            },
+    /// The round in progress, or the last finished round between rounds.
+    pub fn round(&self) -> &Round {
+        &self.round
+    }
+
+    pub fn rules(&self) -> &TableRules {
+        &self.rules
+    }
+
+    pub fn shoe(&self) -> &dyn Shoe {
+        self.shoe.as_ref()
+    }
+
+    /// Swaps in a new shoe and returns the old one. A round stopped by `ShoeError::Exhausted` can
+    /// carry on from the same decision with the new shoe.
+    pub fn set_shoe(&mut self, shoe: Box<dyn Shoe>) -> Box<dyn Shoe> {
+        std::mem::replace(&mut self.shoe, shoe)
+    }
+
+    /// Shuffles the cards from earlier rounds back into the shoe.
+    pub fn reshuffle(&mut self) -> Result<(), GameError> {
+        if self.stage != Stage::Betting {
+            return Err(GameError::RoundInProgress);
         }
+        self.shoe.reshuffle();
+        Ok(())
     }
 
-    pub fn set_bet(
+    pub fn balance(&self, id: PlayerId) -> Result<Money, PlayerBalanceError> {
+        Ok(*self.account(id)?.balance.borrow())
+    }
+
+    pub fn set_balance(&mut self, id: PlayerId, balance: Money) -> Result<(), PlayerBalanceError> {
+        if balance < Money::ZERO {
+            return Err(BalanceCannotBeNegative);
+        }
+        let change = balance - self.balance(id)?;
+        self.modify_balance(id, change)
+    }
+
+    pub fn modify_balance(
         &mut self,
-        player_index: usize,
-        hand_index: usize,
-        amount: f64,
+        id: PlayerId,
+        amount: Money,
     ) -> Result<(), PlayerBalanceError> {
-        self.get_bet(player_index, hand_index)?;
+        let account = self.account(id)?;
+        if -amount > *account.balance.borrow() {
+            return Err(BalanceCannotBeNegative);
+        }
+        *account.balance.borrow_mut() += amount;
+        self.ledger
+            .borrow_mut()
+            .record(id, None, None, Reason::Adjustment, amount);
+        Ok(())
+    }
 
-        self.modify_player_balance(player_index, -amount)?;
+    pub fn ledger(&self) -> Ref<'_, Ledger> {
+        self.ledger.borrow()
+    }
 
-        self.player_bets[player_index][hand_index] = amount;
-        Ok(())
+    pub fn get_bet(&self, seat: usize) -> Result<Money, PlayerBalanceError> {
+        match self.seats.get(seat) {
+            None => Err(SeatDoesNotExist),
+            Some(None) => Err(SeatNotTaken),
+            Some(Some(x)) => Ok(x.bet),
+        }
     }
 
-    pub fn add_bet(&mut self, player_index: usize, amount: f64) -> Result<(), PlayerBalanceError> {
-        if player_index >= self.player_balances.len() {
-            return Err(PlayerDoesNotExist);
+    /// Sets the bet for a seat's next hand. It's taken from the player's balance when the hand is
+    /// dealt.
+    pub fn set_bet(&mut self, seat: usize, amount: Money) -> Result<(), PlayerBalanceError> {
+        let id = self.player_at(seat)?.ok_or(SeatNotTaken)?;
+        self.rules.limits.check(amount)?;
+
+        let total = self
+            .seats_of(id)
+            .into_iter()
+            .filter(|&x| x != seat)
+            .map(|x| self.seats[x].map_or(Money::ZERO, |x| x.bet))
+            .sum::<Money>()
+            + amount;
+        if total > self.rules.limits.maximum_per_player {
+            return Err(PlayerMaximumExceeded);
+        }
+        if total > self.balance(id)? {
+            return Err(BalanceCannotBeNegative);
         }
 
-        self.modify_player_balance(player_index, -amount)?;
-        self.player_bets[player_index].push(amount);
+        if let Some(x) = self.seats[seat].as_mut() {
+            x.bet = amount;
+        }
         Ok(())
     }
 
-    fn create_player_hands(&mut self) -> Vec<PlayerHand> {
+    pub fn clear_bet(&mut self, seat: usize) -> Result<(), PlayerBalanceError> {
+        match self.seats.get_mut(seat) {
+            None => Err(SeatDoesNotExist),
+            Some(None) => Err(SeatNotTaken),
+            Some(Some(x)) => {
+                x.bet = Money::ZERO;
+                Ok(())
+            }
+        }
+    }
+
+    /// With `players_only`, seats whose player has no `Player` to decide for them aren't dealt in.
+    fn create_player_hands(
+        &self,
+        players_only: bool,
+    ) -> Result<Vec<PlayerHand>, PlayerBalanceError> {
         let mut player_hands = Vec::<PlayerHand>::new();
-        for (i, x) in self.player_bets.iter().enumerate() {
-            for y in x {
-                player_hands.push(PlayerHand {
-                    hand: Hand::new(&mut self.shoe),
-                    player_balance: self.player_balances[i].clone(),
-                    bet_amount: *y,
-                    split: false,
-                })
+        let mut bets = vec![Money::ZERO; self.players.len()];
+        for (index, seat) in self.seats.iter().enumerate() {
+            let seat = match seat {
+                None => continue,
+                Some(x) => x,
+            };
+            let account = match &self.players[seat.player.0] {
+                Some(x) if !x.sitting_out && (!players_only || x.player.is_some()) => x,
+                _ => continue,
+            };
+            // a zero bet means the seat isn't played this round
+            if seat.bet == Money::ZERO {
+                continue;
+            }
+            bets[seat.player.0] += seat.bet;
+            if bets[seat.player.0] > *account.balance.borrow() {
+                return Err(BalanceCannotBeNegative);
             }
+
+            player_hands.push(PlayerHand {
+                hand: Hand::empty(),
+                player_balance: account.balance.clone(),
+                ledger: self.ledger.clone(),
+                bet_amount: seat.bet,
+                insurance: Money::ZERO,
+                original_bet: seat.bet,
+                split: false,
+                seat: index,
+                hand_index: 0,
+                player: seat.player,
+            })
         }
-        player_hands
+        Ok(player_hands)
     }
 
-    fn play_round(&mut self) -> Round {
-        let mut round = Round {
-            player_hands: self.create_player_hands(),
-            dealer: Hand::new(&mut self.shoe),
+    /// Deals a new round using the current bets. Nothing is dealt if a player's balance no longer
+    /// covers their bets.
+    pub fn begin_round(&mut self) -> Result<(), GameError> {
+        self.deal_round(false)
+    }
+
+    fn deal_round(&mut self, players_only: bool) -> Result<(), GameError> {
+        if self.stage != Stage::Betting {
+            return Err(GameError::RoundInProgress);
+        }
+
+        let mut player_hands = self.create_player_hands(players_only)?;
+        if player_hands.is_empty() {
+            return Err(GameError::NoBets);
+        }
+
+        // one card to each hand in seat order and the dealer's up card, then the second cards and
+        // the hole card
+        let mut dealer = Hand::empty();
+        for card in 0..2 {
+            for player_hand in &mut player_hands {
+                player_hand.hand.deal_card(&mut self.shoe)?;
+            }
+            if card == 0 || self.rules.hole_card == HoleCardRule::Peek {
+                dealer.deal_card(&mut self.shoe)?;
+            }
+        }
+        for player_hand in &mut player_hands {
+            player_hand.transfer(-player_hand.bet_amount, Reason::Bet);
+        }
+        self.round = Round {
+            player_hands,
+            dealer,
             active_hand_index: 0,
         };
+        self.stage = Stage::EarlySurrender(0);
+        self.advance();
+        Ok(())
+    }
 
-        round.start();
+    /// The decision the active hand of the round is waiting on, if any.
+    pub fn pending_decision(&self) -> Option<Decision> {
+        let player_hand = self.round.player_hands.get(self.round.active_hand_index)?;
+        match self.stage {
+            Stage::EarlySurrender(_) => Some(Decision::EarlySurrender),
+            Stage::Insurance(_) => Some(Decision::Insurance(player_hand.max_insurance())),
+            Stage::Playing => Some(Decision::Action(
+                player_hand
+                    .get_possible_actions(&self.rules, self.round.seat_hands(player_hand.seat)),
+            )),
+            Stage::Betting | Stage::DealerTurn => None,
+        }
+    }
 
-        while round.update_active_hand_index() {
-            let active_player_hand = &mut round.player_hands[round.active_hand_index];
-            let possible_actions = active_player_hand.get_possible_actions();
-            let action = (self.get_action)(&round, possible_actions).action();
-            if let Some(player_hand) =
-                round.player_hands[round.active_hand_index].take_action(action, &mut self.shoe)
-            {
-                round
-                    .player_hands
-                    .insert(round.active_hand_index + 1, player_hand)
+    pub fn apply(&mut self, response: Response) -> Result<(), GameError> {
+        let index = self.round.active_hand_index;
+        match (self.stage, response) {
+            (Stage::Betting, _) => return Err(GameError::NoRoundInProgress),
+            (Stage::EarlySurrender(i), Response::EarlySurrender(surrender)) => {
+                if surrender {
+                    self.round.player_hands[i].surrender(&self.rules);
+                }
+                self.stage = Stage::EarlySurrender(i + 1);
             }
+            (Stage::Insurance(i), Response::Insurance(amount)) => {
+                if amount < Money::ZERO || amount > self.round.player_hands[i].max_insurance() {
+                    return Err(GameError::InvalidResponse);
+                }
+                self.round.player_hands[i].insure(amount);
+                self.stage = Stage::Insurance(i + 1);
+            }
+            (Stage::Playing, Response::Action(action)) => {
+                if !self.allows(&action) {
+                    return Err(GameError::InvalidResponse);
+                }
+                let seat_hands = self.round.seat_hands(self.round.player_hands[index].seat);
+                if let Some(player_hand) = self.round.player_hands[index].take_action(
+                    action.action(),
+                    &mut self.shoe,
+                    &self.rules,
+                    seat_hands,
+                )? {
+                    self.round.player_hands.insert(index + 1, player_hand)
+                }
+            }
+            _ => return Err(GameError::InvalidResponse),
         }
+        self.advance();
+        Ok(())
+    }
 
-        if round
+    /// Plays the dealer's hand, settles the bets and passes the used cards back to the shoe.
+    pub fn finish_round(&mut self) -> Result<&Round, GameError> {
+        match self.stage {
+            Stage::Betting => return Err(GameError::NoRoundInProgress),
+            Stage::DealerTurn => {}
+            _ => return Err(GameError::DecisionPending),
+        }
+
+        if self
+            .round
             .player_hands
             .iter()
             .any(|player_hand| player_hand.hand.status == Stood)
         {
-            round.dealer.dealer_turn(&mut self.shoe);
-            round.end();
+            self.round.dealer.dealer_turn(&mut self.shoe, &self.rules)?;
+            self.round.end(&self.rules);
+        } else if self.rules.hole_card != HoleCardRule::Peek
+            && self.round.player_hands.iter().any(|player_hand| {
+                player_hand.hand.status == Blackjack || player_hand.insurance > Money::ZERO
+            })
+        {
+            self.round.dealer.deal_card(&mut self.shoe)?;
+            self.round.end(&self.rules);
+        }
+
+        self.shoe.new_round(self.round.discards());
+        self.stage = Stage::Betting;
+        self.rounds_played += 1;
+        self.ledger.borrow_mut().next_round();
+        Ok(&self.round)
+    }
+
+    fn allows(&self, action: &PossibleAction) -> bool {
+        match self.pending_decision() {
+            Some(Decision::Action(possible)) => possible.iter().any(|x| match action.action() {
+                Action::Double(amount) => x.double_for(amount).as_ref() == Some(action),
+                _ => x == action,
+            }),
+            _ => false,
+        }
+    }
+
+    fn advance(&mut self) {
+        loop {
+            match self.stage {
+                Stage::EarlySurrender(i) => {
+                    let offered = self
+                        .rules
+                        .surrender
+                        .early_against(self.round.dealer.cards[0].value());
+                    match (i..self.round.player_hands.len())
+                        .find(|&x| offered && self.round.player_hands[x].can_surrender_early())
+                    {
+                        Some(x) => {
+                            self.round.active_hand_index = x;
+                            self.stage = Stage::EarlySurrender(x);
+                            return;
+                        }
+                        None => self.stage = Stage::Insurance(0),
+                    }
+                }
+                Stage::Insurance(i) => {
+                    let offered = self.rules.insurance && self.round.dealer.cards[0].is_ace();
+                    match (i..self.round.player_hands.len()).find(|&x| {
+                        offered && self.round.player_hands[x].max_insurance() > Money::ZERO
+                    }) {
+                        Some(x) => {
+                            self.round.active_hand_index = x;
+                            self.stage = Stage::Insurance(x);
+                            return;
+                        }
+                        None => {
+                            self.round.active_hand_index = 0;
+                            self.round.start(&self.rules);
+                            self.stage = Stage::Playing;
+                        }
+                    }
+                }
+                Stage::Playing => {
+                    if !self.round.update_active_hand_index() {
+                        self.stage = Stage::DealerTurn;
+                    }
+                    return;
+                }
+                Stage::Betting | Stage::DealerTurn => return,
+            }
+        }
+    }
+}
+
+fn empty_round() -> Round {
+    Round {
+        player_hands: vec![],
+        active_hand_index: 0,
+        dealer: Hand {
+            cards: vec![],
+            status: Value,
+            value: 0,
+            soft: false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod game_tests {
+    use crate::game::{Game, GameError, PlayerBalanceError, SessionSummary};
+    use crate::player_hand::PlayerHand;
+    use crate::shoe::{CustomShoe, Penetration, ShoeError, StandardShoe};
+    use crate::HandStatus::{Bust, Lose, Win};
+    use crate::{
+        Action, Card, Decision, HoleCardRule, Money, Player, PlayerId, PossibleAction, Reason,
+        Response, Round, TableRules,
+    };
+    use std::ops::ControlFlow;
+
+    fn test_game(cards: &str) -> (Game, PlayerId) {
+        let mut game = Game::new(
+            Box::new(cards.parse::<CustomShoe>().unwrap()),
+            TableRules::default(),
+        );
+        let id = game.join("Ann", Money::from_dollars(100), None).unwrap();
+        game.take_seat(id, 0).unwrap();
+        game.set_bet(0, Money::from_dollars(10)).unwrap();
+        (game, id)
+    }
+
+    fn choose(game: &Game, action: Action) -> Response {
+        match game.pending_decision() {
+            Some(Decision::Action(possible)) => {
+                Response::Action(possible.into_iter().find(|x| *x == action).unwrap())
+            }
+            x => panic!("expected an action decision, got {:?}", x),
+        }
+    }
+
+    #[test]
+    fn step_through_round() {
+        let (mut game, id) = test_game("10 9 8 7 5");
+        game.begin_round().unwrap();
+        assert_eq!(game.round().dealer.cards.len(), 2);
+        assert_eq!(game.balance(id), Ok(Money::from_dollars(90)));
+
+        game.apply(choose(&game, Action::Stand)).unwrap();
+        assert!(game.pending_decision().is_none());
+
+        let round = game.finish_round().unwrap();
+        assert_eq!(round.dealer.value, 21);
+        assert_eq!(round.player_hands[0].hand.status, Lose);
+        assert_eq!(game.balance(id), Ok(Money::from_dollars(90)));
+        assert!(game.pending_decision().is_none());
+    }
+
+    #[test]
+    fn insurance_decision_before_play() {
+        let (mut game, id) = test_game("10 A 9 7");
+        game.begin_round().unwrap();
+        assert!(matches!(
+            game.pending_decision(),
+            Some(Decision::Insurance(x)) if x == Money::from_dollars(5)
+        ));
+
+        assert_eq!(
+            game.apply(Response::Insurance(Money::from_cents(-1))),
+            Err(GameError::InvalidResponse)
+        );
+        assert_eq!(
+            game.apply(Response::Insurance(Money::from_cents(501))),
+            Err(GameError::InvalidResponse)
+        );
+        assert!(matches!(
+            game.pending_decision(),
+            Some(Decision::Insurance(_))
+        ));
+
+        game.apply(Response::Insurance(Money::ZERO)).unwrap();
+        game.apply(choose(&game, Action::Stand)).unwrap();
+        game.finish_round().unwrap();
+        assert_eq!(game.round().player_hands[0].hand.status, Win);
+        assert_eq!(game.balance(id), Ok(Money::from_dollars(110)));
+    }
+
+    #[test]
+    fn out_of_order_calls() {
+        let (mut game, _) = test_game("10 9 8 7 5 10 9 8 7");
+        assert_eq!(
+            game.finish_round().err(),
+            Some(GameError::NoRoundInProgress)
+        );
+        assert_eq!(
+            game.apply(Response::EarlySurrender(true)),
+            Err(GameError::NoRoundInProgress)
+        );
+
+        game.begin_round().unwrap();
+        assert_eq!(game.begin_round(), Err(GameError::RoundInProgress));
+        assert_eq!(game.finish_round().err(), Some(GameError::DecisionPending));
+        assert_eq!(
+            game.apply(Response::Insurance(Money::from_dollars(5))),
+            Err(GameError::InvalidResponse)
+        );
+        assert_eq!(
+            game.apply(Response::Action(PossibleAction(Action::Double(
+                Money::from_dollars(20)
+            )))),
+            Err(GameError::InvalidResponse)
+        );
+
+        game.apply(Response::Action(PossibleAction(Action::Double(
+            Money::from_dollars(5),
+        ))))
+        .unwrap();
+        game.finish_round().unwrap();
+        game.begin_round().unwrap();
+    }
+
+    struct Hitter {
+        bet: Money,
+        rounds: usize,
+    }
+
+    impl Player for Hitter {
+        fn place_bet(&mut self, game: &Game, _seat: usize) -> ControlFlow<(), Money> {
+            if game.rounds_played() < self.rounds {
+                ControlFlow::Continue(self.bet)
+            } else {
+                ControlFlow::Break(())
+            }
+        }
+
+        fn decide(&mut self, _round: &Round, possible: Vec<PossibleAction>) -> PossibleAction {
+            possible.into_iter().find(|x| *x == Action::Hit).unwrap()
+        }
+
+        fn insurance(&mut self, _round: &Round, _max_insurance: Money) -> Money {
+            Money::ZERO
         }
+    }
+
+    fn hitter(bet: i64, rounds: usize) -> Option<Box<dyn Player>> {
+        Some(Box::new(Hitter {
+            bet: Money::from_dollars(bet),
+            rounds,
+        }))
+    }
+
+    #[test]
+    fn players_decide_for_their_seats() {
+        // a seat without a Player is left out rather than dealt a hand nobody can play
+        let (mut game, id) = test_game("10 9 10 6 8 7 10 10");
+        assert!(game.play_round().unwrap().is_break());
+        assert_eq!(game.balance(id), Ok(Money::from_dollars(100)));
+        game.begin_round().unwrap();
+
+        let mut game = Game::new(
+            Box::new("10 9 10 6 8 7 10 10".parse::<CustomShoe>().unwrap()),
+            TableRules::default(),
+        );
+        let ann = game
+            .join("Ann", Money::from_dollars(100), hitter(10, 1))
+            .unwrap();
+        let bob = game
+            .join("Bob", Money::from_dollars(100), hitter(20, 1))
+            .unwrap();
+        game.take_seat(bob, 2).unwrap();
+        game.take_seat(ann, 5).unwrap();
+
+        let round = match game.play_round().unwrap() {
+            ControlFlow::Continue(round) => round,
+            ControlFlow::Break(()) => panic!("every player bet"),
+        };
+        assert_eq!(round.player_hands[0].player, bob);
+        assert_eq!(round.player_hands[0].hand.status, Bust);
+        assert_eq!(round.player_hands[1].player, ann);
+        assert_eq!(round.player_hands[1].bet_amount, Money::from_dollars(10));
+        assert_eq!(game.balance(ann), Ok(Money::from_dollars(90)));
+        assert_eq!(game.balance(bob), Ok(Money::from_dollars(80)));
+
+        assert!(game.play_round().unwrap().is_break());
+        assert_eq!(
+            game.summary(),
+            SessionSummary {
+                balances: vec![
+                    ("Ann".to_owned(), Money::from_dollars(90)),
+                    ("Bob".to_owned(), Money::from_dollars(80))
+                ],
+                rounds_played: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn stopping_player_sits_out() {
+        let mut game = Game::new(
+            StandardShoe::with_seed(1, Penetration::Fraction(0.75), 3),
+            TableRules::default(),
+        );
+        let ann = game
+            .join("Ann", Money::from_dollars(100), hitter(10, 1))
+            .unwrap();
+        let bob = game
+            .join("Bob", Money::from_dollars(100), hitter(20, 2))
+            .unwrap();
+        game.take_seat(ann, 0).unwrap();
+        game.take_seat(bob, 1).unwrap();
+
+        assert_eq!(
+            game.play_round()
+                .unwrap()
+                .map_continue(|x| x.player_hands.len()),
+            ControlFlow::Continue(2)
+        );
+        let round = match game.play_round().unwrap() {
+            ControlFlow::Continue(round) => round,
+            ControlFlow::Break(()) => panic!("Bob is still playing"),
+        };
+        assert_eq!(round.player_hands.len(), 1);
+        assert_eq!(round.player_hands[0].player, bob);
+        assert_eq!(game.is_sitting_out(ann), Ok(true));
+        assert_eq!(game.is_sitting_out(bob), Ok(false));
+
+        assert!(game.play_round().unwrap().is_break());
+        assert_eq!(game.is_sitting_out(bob), Ok(true));
+        assert_eq!(game.summary().balances.len(), 2);
+        assert_eq!(game.rounds_played(), 2);
+    }
+
+    #[test]
+    fn session_plays_through_the_shoe() {
+        for seed in 0..5 {
+            let players = (0..7)
+                .map(|_| ("Hitter", Money::from_dollars(1000), hitter(10, 40).unwrap()))
+                .collect();
+            let summary = Game::start_game(
+                StandardShoe::with_seed(1, Penetration::Fraction(0.75), seed),
+                TableRules::default(),
+                players,
+            )
+            .unwrap();
+            assert_eq!(summary.rounds_played, 40);
+        }
+
+        let (mut game, _) = test_game("10 9 8 7");
+        game.begin_round().unwrap();
+        assert_eq!(game.reshuffle(), Err(GameError::RoundInProgress));
+    }
+
+    struct Bettor {
+        bets: Vec<Money>,
+    }
+
+    impl Player for Bettor {
+        fn place_bet(&mut self, _game: &Game, _seat: usize) -> ControlFlow<(), Money> {
+            if self.bets.is_empty() {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(self.bets.remove(0))
+            }
+        }
+
+        fn decide(&mut self, _round: &Round, possible: Vec<PossibleAction>) -> PossibleAction {
+            possible.into_iter().find(|x| *x == Action::Stand).unwrap()
+        }
+
+        fn insurance(&mut self, _round: &Round, _max_insurance: Money) -> Money {
+            Money::ZERO
+        }
+    }
+
+    #[test]
+    fn session_survives_rejected_and_zero_bets() {
+        let bettor = |bets: &[i64]| -> Box<dyn Player> {
+            Box::new(Bettor {
+                bets: bets.iter().map(|&x| Money::from_dollars(x)).collect(),
+            })
+        };
+        let summary = Game::start_game(
+            Box::new("10 9 8 10 10 9 8 10".parse::<CustomShoe>().unwrap()),
+            TableRules::default(),
+            vec![
+                ("Ann", Money::from_dollars(15), bettor(&[10, 10])),
+                ("Bob", Money::from_dollars(100), bettor(&[0, 10])),
+            ],
+        )
+        .unwrap();
+        // Ann loses the first round and can't cover the second, Bob sits the first one out
+        assert_eq!(summary.rounds_played, 2);
+        assert_eq!(
+            summary.balances,
+            vec![
+                ("Ann".to_owned(), Money::from_dollars(5)),
+                ("Bob".to_owned(), Money::from_dollars(90))
+            ]
+        );
+
+        let summary = Game::start_game(
+            Box::new("10 9 8 7".parse::<CustomShoe>().unwrap()),
+            TableRules::default(),
+            vec![("Ann", Money::from_dollars(100), bettor(&[0, 0]))],
+        )
+        .unwrap();
+        assert_eq!(summary.rounds_played, 0);
+    }
+
+    #[test]
+    fn bets_within_table_limits() {
+        let (mut game, id) = test_game("10 9 8 7 5");
+        assert_eq!(
+            game.set_bet(0, Money::ZERO),
+            Err(PlayerBalanceError::BetBelowMinimum)
+        );
+        assert_eq!(
+            game.set_bet(0, Money::from_cents(-500)),
+            Err(PlayerBalanceError::BetBelowMinimum)
+        );
+        assert_eq!(
+            game.set_bet(0, Money::from_cents(250)),
+            Err(PlayerBalanceError::BetNotMultipleOfIncrement)
+        );
+
+        game.take_seat(id, 1).unwrap();
+        game.rules.limits.maximum_per_player = Money::from_dollars(25);
+        assert_eq!(
+            game.set_bet(1, Money::from_dollars(20)),
+            Err(PlayerBalanceError::PlayerMaximumExceeded)
+        );
+        assert_eq!(game.set_bet(0, Money::from_dollars(25)), Ok(()));
+        assert_eq!(game.get_bet(0), Ok(Money::from_dollars(25)));
+
+        game.rules.limits.maximum_per_player = Money::from_dollars(1000);
+        assert_eq!(
+            game.set_bet(1, Money::from_dollars(80)),
+            Err(PlayerBalanceError::BalanceCannotBeNegative)
+        );
+        assert_eq!(game.balance(id), Ok(Money::from_dollars(100)));
+    }
+
+    #[test]
+    fn zero_bets_are_not_dealt() {
+        let (mut game, _) = test_game("10 9 8 7 5 6 10 7");
+        let bob = game.join("Bob", Money::from_dollars(100), None).unwrap();
+        game.take_seat(bob, 1).unwrap();
+        game.begin_round().unwrap();
+        assert_eq!(game.round().player_hands.len(), 1);
+        assert_eq!(game.round().player_hands[0].seat, 0);
+
+        let mut game = Game::new(
+            Box::new("10 9 8 7".parse::<CustomShoe>().unwrap()),
+            TableRules::default(),
+        );
+        let ann = game.join("Ann", Money::from_dollars(100), None).unwrap();
+        game.take_seat(ann, 3).unwrap();
+        assert_eq!(game.begin_round(), Err(GameError::NoBets));
+
+        // a bet the balance no longer covers is reported rather than skipped
+        game.set_bet(3, Money::from_dollars(10)).unwrap();
+        game.set_balance(ann, Money::from_dollars(5)).unwrap();
+        assert_eq!(
+            game.begin_round(),
+            Err(GameError::Balance(
+                PlayerBalanceError::BalanceCannotBeNegative
+            ))
+        );
+        assert_eq!(game.balance(ann), Ok(Money::from_dollars(5)));
+        game.set_bet(3, Money::from_dollars(5)).unwrap();
+        game.begin_round().unwrap();
+    }
+
+    #[test]
+    fn ledger_reconciles_balances() {
+        let (mut game, id) = test_game("6 A 5 7 10 8 10 8 7 10 3");
+        game.begin_round().unwrap();
+        game.apply(Response::Insurance(Money::from_dollars(5)))
+            .unwrap();
+        game.apply(choose(&game, Action::Double(Money::from_dollars(10))))
+            .unwrap();
+        game.finish_round().unwrap();
+        assert_eq!(game.balance(id), Ok(Money::from_dollars(115)));
+
+        let ledger = game.ledger();
+        assert_eq!(
+            ledger.round(1).map(|x| x.reason).collect::<Vec<Reason>>(),
+            vec![
+                Reason::Adjustment,
+                Reason::Bet,
+                Reason::Insurance,
+                Reason::Double,
+                Reason::Payout
+            ]
+        );
+        assert_eq!(
+            ledger.player(id).map(|x| x.amount).sum::<Money>(),
+            Money::from_dollars(115)
+        );
+        assert_eq!(ledger.round(1).last().unwrap().seat, Some(0));
+        drop(ledger);
+
+        game.begin_round().unwrap();
+        game.apply(choose(&game, Action::Split)).unwrap();
+        game.apply(choose(&game, Action::Stand)).unwrap();
+        game.apply(choose(&game, Action::Stand)).unwrap();
+        game.finish_round().unwrap();
+        assert_eq!(game.balance(id), Ok(Money::from_dollars(115)));
+        assert_eq!(
+            game.ledger()
+                .round(2)
+                .map(|x| (x.reason, x.hand_index, x.amount.cents()))
+                .collect::<Vec<(Reason, Option<usize>, i64)>>(),
+            vec![
+                (Reason::Bet, Some(0), -1000),
+                (Reason::Split, Some(1), -1000),
+                (Reason::Payout, Some(0), 2000)
+            ]
+        );
+
+        game.leave(id).unwrap();
+        assert_eq!(
+            game.ledger().player(id).map(|x| x.amount).sum::<Money>(),
+            Money::ZERO
+        );
+    }
+
+    #[test]
+    fn seats_join_and_leave() {
+        let (mut game, ann) = test_game("10 9 5 8 7 6 10 7 8 8 10 7");
+        let bob = game.join("Bob", Money::from_dollars(50), None).unwrap();
+        assert_eq!(
+            game.take_seat(bob, 0),
+            Err(PlayerBalanceError::SeatOccupied)
+        );
+        assert_eq!(
+            game.take_seat(bob, 7),
+            Err(PlayerBalanceError::SeatDoesNotExist)
+        );
+        assert_eq!(
+            game.leave_seat(bob, 0),
+            Err(PlayerBalanceError::SeatNotTaken)
+        );
+        assert_eq!(
+            game.set_bet(4, Money::from_dollars(5)),
+            Err(PlayerBalanceError::SeatNotTaken)
+        );
+
+        game.take_seat(ann, 6).unwrap();
+        game.set_bet(6, Money::from_dollars(10)).unwrap();
+        game.take_seat(bob, 3).unwrap();
+        game.set_bet(3, Money::from_dollars(5)).unwrap();
+        assert_eq!(game.seats_of(ann), vec![0, 6]);
+        assert_eq!(game.player_at(3), Ok(Some(bob)));
+
+        game.sit_out(bob, true).unwrap();
+        game.begin_round().unwrap();
+        assert_eq!(
+            game.round()
+                .player_hands
+                .iter()
+                .map(|x| x.seat)
+                .collect::<Vec<usize>>(),
+            vec![0, 6]
+        );
+        assert_eq!(game.leave(ann), Err(PlayerBalanceError::PlayerInRound));
+        assert_eq!(
+            game.leave_seat(ann, 6),
+            Err(PlayerBalanceError::PlayerInRound)
+        );
+        assert_eq!(game.leave_seat(bob, 3), Ok(()));
+        assert_eq!(game.leave(bob), Ok(Money::from_dollars(50)));
+        assert_eq!(game.player_at(3), Ok(None));
+        assert_eq!(
+            game.balance(bob),
+            Err(PlayerBalanceError::PlayerDoesNotExist)
+        );
+    }
+
+    #[test]
+    fn cards_dealt_round_robin() {
+        let cards = |player_hand: &PlayerHand| -> Vec<u8> {
+            player_hand.hand.cards.iter().map(Card::value).collect()
+        };
+        let (mut game, ann) = test_game("2 3 4 5 6 7 8");
+        game.take_seat(ann, 4).unwrap();
+        game.set_bet(4, Money::from_dollars(10)).unwrap();
+        game.begin_round().unwrap();
+        assert_eq!(cards(&game.round().player_hands[0]), vec![2, 5]);
+        assert_eq!(cards(&game.round().player_hands[1]), vec![3, 6]);
+        assert_eq!(game.round().dealer.cards.len(), 2);
+        assert_eq!(game.round().dealer.value, 11);
+
+        let mut game = Game::new(
+            Box::new("2 3 4 5 6 7 8".parse::<CustomShoe>().unwrap()),
+            TableRules {
+                hole_card: HoleCardRule::OriginalBetsOnly,
+                ..TableRules::default()
+            },
+        );
+        let bob = game.join("Bob", Money::from_dollars(100), None).unwrap();
+        game.take_seat(bob, 0).unwrap();
+        game.set_bet(0, Money::from_dollars(10)).unwrap();
+        game.begin_round().unwrap();
+        assert_eq!(cards(&game.round().player_hands[0]), vec![2, 4]);
+        assert_eq!(game.round().dealer.cards.len(), 1);
+        assert_eq!(game.round().dealer.value, 3);
+    }
+
+    #[test]
+    fn new_shoe_after_exhausted() {
+        let (mut game, id) = test_game("8 9 8 7");
+        game.begin_round().unwrap();
+        assert_eq!(
+            game.apply(choose(&game, Action::Split)),
+            Err(GameError::Shoe(ShoeError::Exhausted))
+        );
+        assert_eq!(game.balance(id), Ok(Money::from_dollars(90)));
 
-        round
+        game.set_shoe(Box::new("3 10 10".parse::<CustomShoe>().unwrap()));
+        game.apply(choose(&game, Action::Split)).unwrap();
+        game.apply(choose(&game, Action::Stand)).unwrap();
+        game.apply(choose(&game, Action::Stand)).unwrap();
+        let round = game.finish_round().unwrap();
+        assert_eq!(round.player_hands.len(), 2);
+        assert_eq!(round.dealer.value, 26);
+        assert_eq!(game.balance(id), Ok(Money::from_dollars(120)));
     }
 }
@@ -1,4 +1,5 @@
-use crate::shoe::Shoe;
+use crate::shoe::{Shoe, ShoeError};
+use crate::{Card, TableRules};
 
 #[derive(PartialEq, PartialOrd, Copy, Clone, Debug)]
 pub enum HandStatus {
@@ -14,35 +15,44 @@ pub enum HandStatus {
 
 #[derive(PartialEq, Debug)]
 pub struct Hand {
-    pub cards: Vec<u8>,
+    pub cards: Vec<Card>,
     pub status: HandStatus,
     pub value: u8,
     pub soft: bool,
 }
 
 impl Hand {
-    pub(crate) fn new(shoe: &mut Box<dyn Shoe>) -> Hand {
-        let mut hand = Hand {
+    /// A hand with no cards yet, for dealing one card at a time around the table.
+    pub(crate) fn empty() -> Hand {
+        Hand {
             cards: Vec::new(),
             status: HandStatus::Value,
             value: 0,
             soft: false,
-        };
+        }
+    }
 
-        hand.deal_card(shoe);
-        hand.deal_card(shoe);
-        hand
+    #[cfg(test)]
+    pub(crate) fn new(shoe: &mut Box<dyn Shoe>) -> Result<Hand, ShoeError> {
+        let mut hand = Hand::empty();
+        hand.deal_card(shoe)?;
+        hand.deal_card(shoe)?;
+        Ok(hand)
     }
 
-    pub(crate) fn deal_card(&mut self, shoe: &mut Box<dyn Shoe>) -> u8 {
-        self.cards.push(shoe.deal());
+    pub(crate) fn deal_card(&mut self, shoe: &mut Box<dyn Shoe>) -> Result<u8, ShoeError> {
+        self.add_card(shoe.deal()?);
+        Ok(self.value)
+    }
+
+    pub(crate) fn add_card(&mut self, card: Card) {
+        self.cards.push(card);
         self.calculate_value();
-        self.value
     }
 
     pub(crate) fn calculate_value(&mut self) {
-        let aces = self.cards.iter().filter(|&n| *n == 1).count();
-        self.value = self.cards.iter().sum::<u8>();
+        let aces = self.cards.iter().filter(|card| card.is_ace()).count();
+        self.value = self.cards.iter().map(Card::value).sum::<u8>();
 
         self.soft = self.value < 12 && aces > 0;
         if self.soft {
@@ -62,10 +72,15 @@ impl Hand {
         }
     }
 
-    pub(crate) fn dealer_turn(&mut self, shoe: &mut Box<dyn Shoe>) {
-        while self.value < 17 {
-            self.deal_card(shoe);
+    pub(crate) fn dealer_turn(
+        &mut self,
+        shoe: &mut Box<dyn Shoe>,
+        rules: &TableRules,
+    ) -> Result<(), ShoeError> {
+        while self.value < 17 || (rules.dealer_hits_soft_17 && self.value == 17 && self.soft) {
+            self.deal_card(shoe)?;
         }
+        Ok(())
     }
 }
 
@@ -73,10 +88,11 @@ impl Hand {
 mod hand_tests {
     use super::*;
     use crate::shoe::CustomShoe;
+    use crate::HoleCardRule;
 
     #[test]
     fn hand_value_calculation() {
-        let mut shoe: Box<dyn Shoe> = CustomShoe::new(vec![7, 7, 7, 7]);
+        let mut shoe: Box<dyn Shoe> = Box::new("7 7 7 7".parse::<CustomShoe>().unwrap());
 
         let mut hand = Hand {
             cards: vec![],
@@ -85,22 +101,22 @@ mod hand_tests {
             soft: false,
         };
 
-        assert_eq!(hand.deal_card(&mut shoe), 7);
+        assert_eq!(hand.deal_card(&mut shoe), Ok(7));
         assert_eq!(hand.status, HandStatus::Value);
 
-        assert_eq!(hand.deal_card(&mut shoe), 14);
+        assert_eq!(hand.deal_card(&mut shoe), Ok(14));
         assert_eq!(hand.status, HandStatus::Value);
 
-        assert_eq!(hand.deal_card(&mut shoe), 21);
+        assert_eq!(hand.deal_card(&mut shoe), Ok(21));
         assert_eq!(hand.status, HandStatus::Stood);
 
-        assert_eq!(hand.deal_card(&mut shoe), 28);
+        assert_eq!(hand.deal_card(&mut shoe), Ok(28));
         assert_eq!(hand.status, HandStatus::Bust);
     }
 
     #[test]
     fn soft_hand_value_calculation() {
-        let mut shoe: Box<dyn Shoe> = CustomShoe::new(vec![1, 9, 1, 1, 7]);
+        let mut shoe: Box<dyn Shoe> = Box::new("7 A A 9 A".parse::<CustomShoe>().unwrap());
 
         let mut hand = Hand {
             cards: vec![],
@@ -109,35 +125,111 @@ mod hand_tests {
             soft: false,
         };
 
-        assert_eq!(hand.deal_card(&mut shoe), 7);
+        assert_eq!(hand.deal_card(&mut shoe), Ok(7));
         assert!(!hand.soft);
 
-        assert_eq!(hand.deal_card(&mut shoe), 18);
+        assert_eq!(hand.deal_card(&mut shoe), Ok(18));
         assert!(hand.soft);
 
-        assert_eq!(hand.deal_card(&mut shoe), 19);
+        assert_eq!(hand.deal_card(&mut shoe), Ok(19));
         assert!(hand.soft);
 
-        assert_eq!(hand.deal_card(&mut shoe), 18);
+        assert_eq!(hand.deal_card(&mut shoe), Ok(18));
         assert!(!hand.soft);
 
-        assert_eq!(hand.deal_card(&mut shoe), 19);
+        assert_eq!(hand.deal_card(&mut shoe), Ok(19));
         assert!(!hand.soft);
     }
 
+    #[test]
+    fn face_card_value_calculation() {
+        let mut shoe: Box<dyn Shoe> = Box::new("Q K A".parse::<CustomShoe>().unwrap());
+
+        let mut hand = Hand::new(&mut shoe).unwrap();
+        assert_eq!(hand.value, 20);
+        assert_eq!(hand.deal_card(&mut shoe), Ok(21));
+        assert_eq!(hand.status, HandStatus::Stood);
+    }
+
     #[test]
     fn new_hand_blackjack() {
-        let mut shoe: Box<dyn Shoe> = CustomShoe::new(vec![10, 1]);
+        let mut shoe: Box<dyn Shoe> = Box::new("A 10".parse::<CustomShoe>().unwrap());
 
-        let hand = Hand::new(&mut shoe);
+        let hand = Hand::new(&mut shoe).unwrap();
         assert_eq!(hand.status, HandStatus::Blackjack);
     }
     #[test]
     fn hand_hit_twenty_one() {
-        let mut shoe: Box<dyn Shoe> = CustomShoe::new(vec![8, 8, 5]);
+        let mut shoe: Box<dyn Shoe> = Box::new("5 8 8".parse::<CustomShoe>().unwrap());
 
-        let mut hand = Hand::new(&mut shoe);
-        hand.deal_card(&mut shoe);
+        let mut hand = Hand::new(&mut shoe).unwrap();
+        hand.deal_card(&mut shoe).unwrap();
         assert_eq!(hand.status, HandStatus::Stood);
     }
+
+    #[test]
+    fn dealer_stands_on_soft_seventeen() {
+        let mut shoe: Box<dyn Shoe> = Box::new("A 6 4".parse::<CustomShoe>().unwrap());
+
+        let mut hand = Hand::new(&mut shoe).unwrap();
+        hand.dealer_turn(&mut shoe, &TableRules::default()).unwrap();
+        assert_eq!(
+            hand.cards.iter().map(Card::value).collect::<Vec<u8>>(),
+            vec![1, 6]
+        );
+        assert_eq!(hand.value, 17);
+    }
+
+    #[test]
+    fn dealer_hits_soft_seventeen() {
+        let mut shoe: Box<dyn Shoe> = Box::new("A 6 4".parse::<CustomShoe>().unwrap());
+        let rules = TableRules {
+            dealer_hits_soft_17: true,
+            ..TableRules::default()
+        };
+
+        let mut hand = Hand::new(&mut shoe).unwrap();
+        hand.dealer_turn(&mut shoe, &rules).unwrap();
+        assert_eq!(
+            hand.cards.iter().map(Card::value).collect::<Vec<u8>>(),
+            vec![1, 6, 4]
+        );
+        assert_eq!(hand.value, 21);
+    }
+
+    #[test]
+    fn dealer_hits_soft_seventeen_stands_on_hard_seventeen() {
+        let mut shoe: Box<dyn Shoe> = Box::new("10 7 4".parse::<CustomShoe>().unwrap());
+        let rules = TableRules {
+            dealer_hits_soft_17: true,
+            ..TableRules::default()
+        };
+
+        let mut hand = Hand::new(&mut shoe).unwrap();
+        hand.dealer_turn(&mut shoe, &rules).unwrap();
+        assert_eq!(
+            hand.cards.iter().map(Card::value).collect::<Vec<u8>>(),
+            vec![10, 7]
+        );
+    }
+
+    #[test]
+    fn dealer_without_hole_card() {
+        let mut shoe: Box<dyn Shoe> = Box::new("A 10".parse::<CustomShoe>().unwrap());
+        let rules = TableRules {
+            hole_card: HoleCardRule::OriginalBetsOnly,
+            ..TableRules::default()
+        };
+
+        let mut hand = Hand::empty();
+        hand.deal_card(&mut shoe).unwrap();
+        assert_eq!(
+            hand.cards.iter().map(Card::value).collect::<Vec<u8>>(),
+            vec![1]
+        );
+        assert_eq!(hand.status, HandStatus::Value);
+
+        hand.dealer_turn(&mut shoe, &rules).unwrap();
+        assert_eq!(hand.status, HandStatus::Blackjack);
+    }
 }
@@ -0,0 +1,132 @@
+use crate::{Money, PlayerId};
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Reason {
+    Bet,
+    Double,
+    Split,
+    Insurance,
+    Payout,
+    InsurancePayout,
+    SurrenderRefund,
+    /// Balances set or changed directly through `Game`.
+    Adjustment,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct LedgerEntry {
+    pub player: PlayerId,
+    /// The seat the hand was played from, or `None` for adjustments.
+    pub seat: Option<usize>,
+    /// The hand's index within its seat, which tells split hands apart.
+    pub hand_index: Option<usize>,
+    pub round: usize,
+    pub reason: Reason,
+    /// Positive for credits to the player, negative for debits.
+    pub amount: Money,
+}
+
+/// An append-only record of every change to the players' balances.
+#[derive(Debug)]
+pub struct Ledger {
+    entries: Vec<LedgerEntry>,
+    round: usize,
+}
+
+impl Ledger {
+    pub(crate) fn new() -> Ledger {
+        Ledger {
+            entries: vec![],
+            round: 1,
+        }
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        player: PlayerId,
+        seat: Option<usize>,
+        hand_index: Option<usize>,
+        reason: Reason,
+        amount: Money,
+    ) {
+        if amount == Money::ZERO {
+            return;
+        }
+        self.entries.push(LedgerEntry {
+            player,
+            seat,
+            hand_index,
+            round: self.round,
+            reason,
+            amount,
+        });
+    }
+
+    pub(crate) fn next_round(&mut self) {
+        self.round += 1;
+    }
+
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    /// The entries for a round, counting from 1.
+    pub fn round(&self, round: usize) -> impl Iterator<Item = &LedgerEntry> {
+        self.entries.iter().filter(move |x| x.round == round)
+    }
+
+    pub fn player(&self, player: PlayerId) -> impl Iterator<Item = &LedgerEntry> {
+        self.entries.iter().filter(move |x| x.player == player)
+    }
+}
+
+#[cfg(test)]
+mod ledger_tests {
+    use super::*;
+
+    #[test]
+    fn ledger_entries_by_round_and_seat() {
+        let mut ledger = Ledger::new();
+        ledger.record(
+            PlayerId(0),
+            None,
+            None,
+            Reason::Bet,
+            Money::from_dollars(-10),
+        );
+        ledger.record(
+            PlayerId(1),
+            None,
+            None,
+            Reason::Bet,
+            Money::from_dollars(-5),
+        );
+        ledger.record(
+            PlayerId(0),
+            Some(0),
+            Some(0),
+            Reason::Payout,
+            Money::from_dollars(20),
+        );
+        ledger.next_round();
+        ledger.record(
+            PlayerId(0),
+            None,
+            None,
+            Reason::Bet,
+            Money::from_dollars(-10),
+        );
+
+        assert_eq!(ledger.entries().len(), 4);
+        assert_eq!(ledger.round(1).count(), 3);
+        assert_eq!(
+            ledger
+                .player(PlayerId(0))
+                .filter(|x| x.round == 1)
+                .map(|x| x.amount)
+                .sum::<Money>(),
+            Money::from_dollars(10)
+        );
+        assert_eq!(ledger.round(2).next().unwrap().round, 2);
+    }
+}
@@ -1,11 +1,24 @@
 mod action;
+mod card;
 mod game;
 mod hand;
+mod ledger;
+mod money;
+mod player;
 mod player_hand;
 mod round;
+mod rules;
+mod seat;
 pub mod shoe;
+mod shuffle;
 
 pub use crate::action::*;
+pub use crate::card::*;
 pub use crate::game::*;
 pub use crate::hand::*;
+pub use crate::ledger::*;
+pub use crate::money::*;
+pub use crate::player::*;
 pub use crate::round::*;
+pub use crate::rules::*;
+pub use crate::seat::PlayerId;
@@ -1,81 +1,181 @@
 use console::Term;
 use std::collections::HashMap;
-use vingt_et_un::shoe::StandardShoe;
-use vingt_et_un::{Action, Game, HandStatus, PlayerBalanceError, PossibleAction, Round};
+use std::ops::ControlFlow;
+use vingt_et_un::shoe::{ShoeError, StandardShoe};
+use vingt_et_un::{
+    Action, Card, Game, GameError, HandStatus, Money, Player, PlayerBalanceError, PossibleAction,
+    Round, TableRules,
+};
 
 fn main() {
     print_banner();
-    Game::start_game(StandardShoe::new(4), new_round, get_action);
+    match Game::start_game(
+        StandardShoe::new(4),
+        TableRules::default(),
+        vec![(
+            "Player",
+            Money::from_dollars(1000),
+            Box::new(TerminalPlayer { quit: false }),
+        )],
+    ) {
+        Ok(summary) => println!(
+            "You played {} rounds and left with ${}.",
+            summary.rounds_played, summary.balances[0].1
+        ),
+        Err(GameError::Shoe(ShoeError::Exhausted)) => println!("The shoe ran out of cards."),
+        Err(x) => println!("The game stopped unexpectedly: {:?}", x),
+    }
 }
 
-fn new_round(game: &mut Game, last_round: &Round) {
-    if game.get_player_balances().is_empty() {
-        // update_player_balance returns an error if balance is negative. this
-        // hardcoded value is positive, so unwrap is safe
-        game.set_player_balances(vec![1000.0]).unwrap();
-    } else {
-        print_game(last_round)
-    }
+struct TerminalPlayer {
+    // set when the player quits in the middle of a round, so the round is finished first
+    quit: bool,
+}
+
+impl Player for TerminalPlayer {
+    fn place_bet(&mut self, game: &Game, seat: usize) -> ControlFlow<(), Money> {
+        if !game.round().player_hands.is_empty() {
+            print_game(game.round())
+        }
+
+        if self.quit {
+            return ControlFlow::Break(());
+        }
+
+        if game.shoe().reshuffled() {
+            println!("The shoe has been shuffled.");
+        }
+
+        // the game only asks for bets on seats that have been taken
+        let id = game.player_at(seat).unwrap().unwrap();
+        let balance = game.balance(id).unwrap();
+        println!("Enter a new bet amount, or (q)uit:");
+        loop {
+            let input = Term::stdout()
+                .read_line_initial_text(&game.get_bet(seat).unwrap().to_string())
+                .unwrap();
+            if input.trim() == "q" {
+                break ControlFlow::Break(());
+            }
 
-    println!("Enter a new bet amount:");
-    loop {
-        match Term::stdout()
-            .read_line_initial_text(&game.get_bet(0, 0).unwrap().to_string())
-            .unwrap()
-            .parse()
-        {
-            Ok(x) => match game.set_bet(0, 0, x) {
-                Ok(_) => break,
-                Err(x) if matches!(x, PlayerBalanceError::BalanceCannotBeNegative) => {
+            let limits = game.rules().limits;
+            match input.parse::<Money>() {
+                Ok(x) if x > balance => {
                     println!("Your balance is too low to bet that much. Enter lower bet.")
                 }
-                _ => panic!(), // player index and hand index of zero should always work
-            },
-            Err(_) => println!("That wasn't a valid number. Try again."),
+                Ok(x) => match limits.check(x) {
+                    Ok(_) => break ControlFlow::Continue(x),
+                    Err(PlayerBalanceError::BetBelowMinimum) => {
+                        println!("The table minimum is ${}.", limits.minimum)
+                    }
+                    Err(PlayerBalanceError::BetAboveMaximum) => {
+                        println!("The table maximum is ${}.", limits.maximum)
+                    }
+                    Err(_) => println!("Bets must be in multiples of ${}.", limits.increment),
+                },
+                Err(_) => println!("That wasn't a valid number. Try again."),
+            }
         }
     }
-}
 
-fn get_action(game: &Round, possible: Vec<PossibleAction>) -> PossibleAction {
-    print_game(game);
+    fn decide(&mut self, round: &Round, possible: Vec<PossibleAction>) -> PossibleAction {
+        if self.quit {
+            // stand is always possible
+            return possible.into_iter().find(|x| *x == Action::Stand).unwrap();
+        }
 
-    let mut prompt = "Choose an action: ".to_owned();
-    let mut possible_actions = HashMap::<char, PossibleAction>::new();
-    for i in possible {
-        match i.action() {
-            Action::Hit => {
-                possible_actions.insert('h', i);
-                prompt += "(h)it, "
-            }
-            Action::Stand => {
-                possible_actions.insert('s', i);
-                prompt += "(s)tand, "
-            }
-            Action::Double => {
-                possible_actions.insert('d', i);
-                prompt += "(d)ouble, "
+        print_game(round);
+
+        let mut prompt = "Choose an action: ".to_owned();
+        let mut possible_actions = HashMap::<char, PossibleAction>::new();
+        for i in possible {
+            match i.action() {
+                Action::Hit => {
+                    possible_actions.insert('h', i);
+                    prompt += "(h)it, "
+                }
+                Action::Stand => {
+                    possible_actions.insert('s', i);
+                    prompt += "(s)tand, "
+                }
+                Action::Double(_) => {
+                    possible_actions.insert('d', i);
+                    prompt += "(d)ouble, double f(o)r less, "
+                }
+                Action::Split => {
+                    possible_actions.insert('l', i);
+                    prompt += "sp(l)it, ";
+                }
+                Action::Surrender => {
+                    possible_actions.insert('u', i);
+                    prompt += "s(u)rrender, "
+                }
             }
-            Action::Split => {
-                possible_actions.insert('l', i);
-                prompt += "sp(l)it, ";
+        }
+
+        prompt += "(q)uit";
+        println!("{}", prompt);
+
+        loop {
+            match Term::stdout().read_char().unwrap() {
+                'q' => {
+                    self.quit = true;
+                    break possible_actions.remove(&'s').unwrap();
+                }
+                'o' if possible_actions.contains_key(&'d') => {
+                    println!("Enter the amount to double for:");
+                    match Term::stdout().read_line().unwrap().parse() {
+                        Ok(amount) => match possible_actions[&'d'].double_for(amount) {
+                            None => println!("You can't double for that amount."),
+                            Some(x) => break x,
+                        },
+                        Err(_) => println!("That wasn't a valid number."),
+                    }
+                }
+                c => match possible_actions.remove(&c) {
+                    None => println!("Invalid action"),
+                    Some(x) => break x,
+                },
             }
-            Action::Surrender => {
-                possible_actions.insert('u', i);
-                prompt += "s(u)rrender, "
+        }
+    }
+
+    fn insurance(&mut self, round: &Round, max_insurance: Money) -> Money {
+        if self.quit {
+            return Money::ZERO;
+        }
+
+        print_game(round);
+
+        if round.player_hands[round.active_hand_index].hand.status == HandStatus::Blackjack {
+            println!("Take even money? (y)es, (n)o");
+        } else {
+            println!("Take insurance for ${}? (y)es, (n)o", max_insurance);
+        }
+
+        loop {
+            match Term::stdout().read_char().unwrap() {
+                'y' => break max_insurance,
+                'n' => break Money::ZERO,
+                _ => println!("Invalid choice"),
             }
         }
     }
 
-    prompt += "(q)uit";
-    println!("{}", prompt);
+    fn early_surrender(&mut self, round: &Round) -> bool {
+        if self.quit {
+            return false;
+        }
+
+        print_game(round);
+        println!("Surrender before the dealer checks for blackjack? (y)es, (n)o");
 
-    loop {
-        match Term::stdout().read_char().unwrap() {
-            'q' => std::process::exit(0),
-            c => match possible_actions.remove(&c) {
-                None => println!("Invalid action"),
-                Some(x) => break x,
-            },
+        loop {
+            match Term::stdout().read_char().unwrap() {
+                'y' => break true,
+                'n' => break false,
+                _ => println!("Invalid choice"),
+            }
         }
     }
 }
@@ -85,20 +185,24 @@ fn print_game(round: &Round) {
     println!();
     print!(" Dealer Hand: ");
     if round.active_hand_index != round.player_hands.len() {
-        println!("[?, {}]", round.dealer.cards[1]);
+        if round.dealer.cards.len() == 1 {
+            println!("[{}]", round.dealer.cards[0]);
+        } else {
+            println!("[{}, ?]", round.dealer.cards[0]);
+        }
         round
             .player_hands
             .iter()
             .enumerate()
             .for_each(|(i, position)| {
                 println!(
-                    "{}Player Hand: {:?} {} {}   Bet: ${}",
+                    "{}Player Hand: {} {} {}   Bet: ${}",
                     if round.active_hand_index == i {
                         ">"
                     } else {
                         " "
                     },
-                    position.hand.cards,
+                    cards_message(&position.hand.cards),
                     if position.hand.soft {
                         format!("{}/{}", position.hand.value - 10, position.hand.value)
                     } else {
@@ -110,15 +214,15 @@ fn print_game(round: &Round) {
             });
     } else {
         println!(
-            "{:?} {} {}",
-            round.dealer.cards,
+            "{} {} {}",
+            cards_message(&round.dealer.cards),
             round.dealer.value,
             hand_message(round.dealer.status)
         );
         round.player_hands.iter().for_each(|position| {
             println!(
-                " Player Hand: {:?} {} {}  Bet: ${} ",
-                position.hand.cards,
+                " Player Hand: {} {} {}  Bet: ${} ",
+                cards_message(&position.hand.cards),
                 position.hand.value,
                 hand_message(position.hand.status),
                 position.bet_amount,
@@ -130,6 +234,17 @@ fn print_game(round: &Round) {
     println!();
 }
 
+fn cards_message(cards: &[Card]) -> String {
+    format!(
+        "[{}]",
+        cards
+            .iter()
+            .map(|card| card.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    )
+}
+
 fn hand_message(status: HandStatus) -> &'static str {
     match status {
         HandStatus::Win => "Win",
@@ -0,0 +1,210 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// An exact amount of money, counted in cents.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Default, Debug)]
+pub struct Money(i64);
+
+/// How a payout or refund that doesn't come to a whole number of cents is rounded, e.g. 3:2 on
+/// $0.25 or half of $0.25 on surrender.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Rounding {
+    Down,
+    Up,
+    Nearest,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum ParseMoneyError {
+    InvalidAmount,
+    TooManyDecimals,
+}
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub const fn from_cents(cents: i64) -> Money {
+        Money(cents)
+    }
+
+    pub const fn from_dollars(dollars: i64) -> Money {
+        Money(dollars * 100)
+    }
+
+    pub fn cents(&self) -> i64 {
+        self.0
+    }
+
+    pub fn scale(&self, factor: f64, rounding: Rounding) -> Money {
+        let exact = self.0 as f64 * factor;
+        // products like 1.2 * 1005 aren't exact in f64, so snap them before rounding
+        let cents = if (exact - exact.round()).abs() < 1e-6 {
+            exact.round()
+        } else {
+            match rounding {
+                Rounding::Down => exact.floor(),
+                Rounding::Up => exact.ceil(),
+                Rounding::Nearest => exact.round(),
+            }
+        };
+        Money(cents as i64)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, rhs: Money) -> Money {
+        Money(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, rhs: Money) -> Money {
+        Money(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Money) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl Mul<i64> for Money {
+    type Output = Money;
+
+    fn mul(self, rhs: i64) -> Money {
+        Money(self.0 * rhs)
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.copied().sum()
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let (dollars, cents) = (self.0.abs() / 100, self.0.abs() % 100);
+        if cents == 0 {
+            write!(f, "{}{}", sign, dollars)
+        } else {
+            write!(f, "{}{}.{:02}", sign, dollars, cents)
+        }
+    }
+}
+
+impl FromStr for Money {
+    type Err = ParseMoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(x) => (true, x),
+            None => (false, s),
+        };
+        let (dollars, cents) = s.split_once('.').unwrap_or((s, ""));
+        if cents.len() > 2 {
+            return Err(ParseMoneyError::TooManyDecimals);
+        }
+        if dollars.is_empty() && cents.is_empty()
+            || !dollars
+                .chars()
+                .chain(cents.chars())
+                .all(|x| x.is_ascii_digit())
+        {
+            return Err(ParseMoneyError::InvalidAmount);
+        }
+
+        let parse = |x: &str| x.parse::<i64>().map_err(|_| ParseMoneyError::InvalidAmount);
+        let dollars = if dollars.is_empty() {
+            0
+        } else {
+            parse(dollars)?
+        };
+        let cents = match cents.len() {
+            0 => 0,
+            1 => parse(cents)? * 10,
+            _ => parse(cents)?,
+        };
+        let amount = dollars
+            .checked_mul(100)
+            .and_then(|x| x.checked_add(cents))
+            .ok_or(ParseMoneyError::InvalidAmount)?;
+        Ok(Money(if negative { -amount } else { amount }))
+    }
+}
+
+#[cfg(test)]
+mod money_tests {
+    use super::*;
+
+    #[test]
+    fn money_parse_and_display() {
+        assert_eq!("12".parse(), Ok(Money::from_dollars(12)));
+        assert_eq!("12.5".parse(), Ok(Money::from_cents(1250)));
+        assert_eq!(".05".parse(), Ok(Money::from_cents(5)));
+        assert_eq!("-3.25".parse(), Ok(Money::from_cents(-325)));
+        assert_eq!(
+            "1.005".parse::<Money>(),
+            Err(ParseMoneyError::TooManyDecimals)
+        );
+        assert_eq!("1e3".parse::<Money>(), Err(ParseMoneyError::InvalidAmount));
+        assert_eq!(".".parse::<Money>(), Err(ParseMoneyError::InvalidAmount));
+        assert_eq!(
+            "99999999999999999".parse::<Money>(),
+            Err(ParseMoneyError::InvalidAmount)
+        );
+        assert_eq!(
+            "92233720368547758.07".parse(),
+            Ok(Money::from_cents(i64::MAX))
+        );
+
+        assert_eq!(Money::from_dollars(1000).to_string(), "1000");
+        assert_eq!(Money::from_cents(1205).to_string(), "12.05");
+        assert_eq!(Money::from_cents(-50).to_string(), "-0.50");
+    }
+
+    #[test]
+    fn money_scale_rounding() {
+        let bet = Money::from_cents(25);
+        assert_eq!(bet.scale(1.5, Rounding::Down), Money::from_cents(37));
+        assert_eq!(bet.scale(1.5, Rounding::Up), Money::from_cents(38));
+        assert_eq!(bet.scale(0.5, Rounding::Nearest), Money::from_cents(13));
+        assert_eq!(
+            Money::from_cents(1005).scale(1.2, Rounding::Down),
+            Money::from_cents(1206)
+        );
+        assert_eq!(
+            Money::from_dollars(10).scale(0.5, Rounding::Up),
+            Money::from_dollars(5)
+        );
+    }
+}
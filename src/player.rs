@@ -0,0 +1,20 @@
+use crate::{Game, Money, PossibleAction, Round};
+use std::ops::ControlFlow;
+
+/// Makes the decisions for one seat at the table.
+pub trait Player {
+    /// The bet for the seat's next hand, or `Break` to stop playing. A player who stops sits out
+    /// with all of their seats, and the session ends once nobody is left playing. `game.round()`
+    /// is the last round played.
+    fn place_bet(&mut self, game: &Game, seat: usize) -> ControlFlow<(), Money>;
+
+    fn decide(&mut self, round: &Round, possible: Vec<PossibleAction>) -> PossibleAction;
+
+    /// The amount of insurance to take, up to `max_insurance`. Amounts outside that range are
+    /// clamped to it.
+    fn insurance(&mut self, round: &Round, max_insurance: Money) -> Money;
+
+    fn early_surrender(&mut self, _round: &Round) -> bool {
+        false
+    }
+}
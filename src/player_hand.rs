@@ -1,16 +1,28 @@
-use crate::shoe::Shoe;
+use crate::shoe::{Shoe, ShoeError};
 use crate::Action::{Double, Hit, Split, Stand, Surrender};
 use crate::HandStatus::Stood;
-use crate::{Action, Hand, HandStatus, PossibleAction};
+use crate::{
+    Action, Hand, HandStatus, Ledger, Money, PlayerId, PossibleAction, Reason, Rounding, TableRules,
+};
+use crate::{HoleCardRule, PairRule, SurrenderRule};
 use std::cell::RefCell;
 use std::rc::Rc;
 
 #[derive(Debug)]
 pub struct PlayerHand {
     pub hand: Hand,
-    pub bet_amount: f64,
-    pub(crate) player_balance: Rc<RefCell<f64>>,
+    pub bet_amount: Money,
+    pub insurance: Money,
+    pub(crate) original_bet: Money,
+    pub(crate) player_balance: Rc<RefCell<Money>>,
+    pub(crate) ledger: Rc<RefCell<Ledger>>,
     pub(crate) split: bool,
+    /// The seat the hand is played from. Split hands share their seat.
+    pub seat: usize,
+    /// The hand's index within its seat. Hands made by splitting are numbered in the order the
+    /// splits were made.
+    pub hand_index: usize,
+    pub player: PlayerId,
 }
 
 impl PlayerHand {
@@ -18,26 +30,47 @@ impl PlayerHand {
         &mut self,
         action: Action,
         shoe: &mut Box<dyn Shoe>,
-    ) -> Option<PlayerHand> {
+        rules: &TableRules,
+        seat_hands: usize,
+    ) -> Result<Option<PlayerHand>, ShoeError> {
         match action {
             Stand => self.hand.status = Stood,
             Hit => {
-                self.hand.deal_card(shoe);
+                self.hand.deal_card(shoe)?;
             }
-            Double => self.double(shoe),
-            Split => return Some(self.split(shoe)),
-            Surrender => self.surrender(),
+            Double(amount) => self.double(shoe, amount)?,
+            Split => return Ok(Some(self.split(shoe, rules, seat_hands)?)),
+            Surrender => self.surrender(rules),
         };
-        None
+        Ok(None)
     }
 
-    fn double(&mut self, shoe: &mut Box<dyn Shoe>) {
-        *self.player_balance.borrow_mut() -= self.bet_amount;
-        self.bet_amount *= 2.0;
-        self.hand.deal_card(shoe);
+    fn double(&mut self, shoe: &mut Box<dyn Shoe>, amount: Money) -> Result<(), ShoeError> {
+        let amount = amount.clamp(Money::ZERO, self.max_double());
+        self.hand.deal_card(shoe)?;
+        self.transfer(-amount, Reason::Double);
+        self.bet_amount += amount;
+        if self.hand.status == HandStatus::Value {
+            self.hand.status = Stood;
+        }
+        Ok(())
+    }
+
+    fn max_double(&self) -> Money {
+        self.bet_amount.min(*self.player_balance.borrow())
     }
 
-    pub(crate) fn split(&mut self, shoe: &mut Box<dyn Shoe>) -> PlayerHand {
+    pub(crate) fn split(
+        &mut self,
+        shoe: &mut Box<dyn Shoe>,
+        rules: &TableRules,
+        seat_hands: usize,
+    ) -> Result<PlayerHand, ShoeError> {
+        // deal both cards before anything changes so an exhausted shoe leaves the hand as it was.
+        // A card dealt before the error is lost, the same as a card that misses the discard tray.
+        let first = shoe.deal()?;
+        let second = shoe.deal()?;
+
         let mut new_hand = PlayerHand {
             hand: Hand {
                 cards: vec![self.hand.cards.pop().unwrap()],
@@ -46,45 +79,145 @@ impl PlayerHand {
                 soft: false,
             },
             bet_amount: self.bet_amount,
+            insurance: Money::ZERO,
+            original_bet: self.original_bet,
             player_balance: self.player_balance.clone(),
+            ledger: self.ledger.clone(),
             split: true,
+            seat: self.seat,
+            hand_index: seat_hands,
+            player: self.player,
         };
-        *self.player_balance.borrow_mut() -= self.bet_amount;
+        new_hand.transfer(-self.bet_amount, Reason::Split);
         self.split = true;
-        self.hand.deal_card(shoe);
-        new_hand.hand.deal_card(shoe);
-        new_hand
+        self.hand.add_card(first);
+        new_hand.hand.add_card(second);
+        self.finish_split(rules);
+        new_hand.finish_split(rules);
+        Ok(new_hand)
+    }
+
+    fn finish_split(&mut self, rules: &TableRules) {
+        // 21 on a split hand is an ordinary 21, not a blackjack
+        if self.hand.status == HandStatus::Blackjack {
+            self.hand.status = Stood;
+        }
+
+        if self.split_aces(rules) && !(self.hand.cards[1].is_ace() && rules.resplit_aces) {
+            self.hand.status = Stood;
+        }
+    }
+
+    fn split_aces(&self, rules: &TableRules) -> bool {
+        self.split && self.hand.cards[0].is_ace() && !rules.hit_split_aces
     }
 
-    fn surrender(&mut self) {
-        *self.player_balance.borrow_mut() += self.bet_amount / 2.0;
+    pub(crate) fn surrender(&mut self, rules: &TableRules) {
+        self.transfer(
+            self.bet_amount.scale(0.5, rules.rounding),
+            Reason::SurrenderRefund,
+        );
         self.hand.status = HandStatus::Surrender;
     }
 
-    pub(crate) fn get_possible_actions(&self) -> Vec<PossibleAction> {
+    fn is_pair(&self, rules: &TableRules) -> bool {
+        let cards = &self.hand.cards;
+        cards.len() == 2
+            && match rules.split_pairs {
+                PairRule::SameValue => cards[0].value() == cards[1].value(),
+                PairRule::SameRank => cards[0].rank == cards[1].rank,
+            }
+    }
+
+    pub(crate) fn can_surrender_early(&self) -> bool {
+        !self.split && self.hand.cards.len() == 2 && self.hand.status == HandStatus::Value
+    }
+
+    pub(crate) fn max_insurance(&self) -> Money {
+        if self.hand.status == HandStatus::Surrender {
+            return Money::ZERO;
+        }
+
+        // a limit, so it never rounds up past half the bet
+        let max_insurance = self.bet_amount.scale(0.5, Rounding::Down);
+        let balance = *self.player_balance.borrow();
+        if self.hand.status == HandStatus::Blackjack {
+            // even money is only offered as a full insurance bet
+            if max_insurance <= balance {
+                max_insurance
+            } else {
+                Money::ZERO
+            }
+        } else {
+            max_insurance.min(balance)
+        }
+    }
+
+    pub(crate) fn insure(&mut self, amount: Money) {
+        let amount = if self.hand.status == HandStatus::Blackjack && amount > Money::ZERO {
+            self.max_insurance()
+        } else {
+            amount.clamp(Money::ZERO, self.max_insurance())
+        };
+        self.transfer(-amount, Reason::Insurance);
+        self.insurance = amount;
+    }
+
+    pub(crate) fn get_possible_actions(
+        &self,
+        rules: &TableRules,
+        seat_hands: usize,
+    ) -> Vec<PossibleAction> {
         let mut possible_actions: Vec<PossibleAction> = Vec::new();
+        let split_aces = self.split_aces(rules);
 
-        possible_actions.push(PossibleAction(Hit));
+        if !split_aces {
+            possible_actions.push(PossibleAction(Hit));
+        }
         possible_actions.push(PossibleAction(Stand));
 
-        if self.bet_amount <= *self.player_balance.borrow() {
-            possible_actions.push(PossibleAction(Double));
+        if self.max_double() > Money::ZERO
+            && self.hand.cards.len() == 2
+            && rules.double.allows(self.hand.value)
+            && !split_aces
+            && (!self.split || rules.double_after_split)
+        {
+            possible_actions.push(PossibleAction(Double(self.max_double())));
         }
 
-        if self.hand.cards.len() == 2
-            && self.hand.cards[0] == self.hand.cards[1]
+        if self.is_pair(rules)
             && self.bet_amount <= *self.player_balance.borrow()
+            && seat_hands < rules.max_split_hands
+            && (!self.split || !self.hand.cards[0].is_ace() || rules.resplit_aces)
         {
             possible_actions.push(PossibleAction(Split));
         }
-        if !self.split && self.hand.cards.len() == 2 {
+        // without a hole card the dealer's blackjack isn't known yet, so a late surrender would
+        // dodge it
+        if rules.surrender != SurrenderRule::None
+            && rules.hole_card == HoleCardRule::Peek
+            && !self.split
+            && self.hand.cards.len() == 2
+        {
             possible_actions.push(PossibleAction(Surrender));
         }
 
         possible_actions
     }
 
-    pub fn balance(&self) -> f64 {
+    /// Credits (or debits, for a negative amount) the player's balance and records it in the ledger.
+    pub(crate) fn transfer(&mut self, amount: Money, reason: Reason) {
+        *self.player_balance.borrow_mut() += amount;
+        self.ledger.borrow_mut().record(
+            self.player,
+            Some(self.seat),
+            Some(self.hand_index),
+            reason,
+            amount,
+        );
+    }
+
+    pub fn balance(&self) -> Money {
         *self.player_balance.borrow()
     }
 }
@@ -92,19 +225,28 @@ impl PlayerHand {
 #[cfg(test)]
 mod player_hand_tests {
     use crate::player_hand::PlayerHand;
-    use crate::shoe::{CustomShoe, Shoe, StandardShoe};
-    use crate::Action::{Double, Split, Surrender};
-    use crate::{Hand, PossibleAction};
+    use crate::shoe::{CustomShoe, Shoe, ShoeError, StandardShoe};
+    use crate::Action::{Double, Hit, Split, Stand, Surrender};
+    use crate::{
+        Card, DoubleRule, Hand, HandStatus, HoleCardRule, Ledger, Money, PairRule, PlayerId,
+        PossibleAction, SurrenderRule, TableRules,
+    };
     use std::cell::RefCell;
     use std::rc::Rc;
 
-    fn test_player_hand(shoe: &mut Box<dyn Shoe>, bet_amount: f64) -> PlayerHand {
-        let balance = Rc::from(RefCell::from(100.00));
+    fn test_player_hand(shoe: &mut Box<dyn Shoe>, bet_amount: Money) -> PlayerHand {
+        let balance = Rc::from(RefCell::from(Money::from_dollars(100)));
         PlayerHand {
-            hand: Hand::new(shoe),
+            hand: Hand::new(shoe).unwrap(),
             player_balance: balance.clone(),
+            ledger: Rc::new(RefCell::new(Ledger::new())),
             bet_amount,
+            insurance: Money::ZERO,
+            original_bet: bet_amount,
             split: false,
+            seat: 0,
+            hand_index: 0,
+            player: PlayerId(0),
         }
     }
 
@@ -112,60 +254,342 @@ mod player_hand_tests {
     fn double_possible_action() {
         let mut shoe: Box<dyn Shoe> = StandardShoe::new(1);
 
-        let mut player_hand = test_player_hand(&mut shoe, 60.0);
+        let rules = TableRules::default();
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(60));
         assert!(player_hand
-            .get_possible_actions()
-            .contains(&PossibleAction(Double)));
+            .get_possible_actions(&rules, 1)
+            .iter()
+            .any(|x| matches!(x.action(), Double(_))));
 
-        player_hand.double(&mut shoe);
+        player_hand
+            .double(&mut shoe, Money::from_dollars(60))
+            .unwrap();
         assert!(!player_hand
-            .get_possible_actions()
-            .contains(&PossibleAction(Double)));
+            .get_possible_actions(&rules, 1)
+            .iter()
+            .any(|x| matches!(x.action(), Double(_))));
     }
 
     #[test]
     fn surrender_possible_action() {
         let mut shoe: Box<dyn Shoe> = StandardShoe::new(1);
 
-        let mut player_hand = test_player_hand(&mut shoe, 0.0);
+        let rules = TableRules::default();
+        let mut player_hand = test_player_hand(&mut shoe, Money::ZERO);
         assert!(player_hand
-            .get_possible_actions()
+            .get_possible_actions(&rules, 1)
             .contains(&PossibleAction(Surrender)));
 
-        let player_hand2 = player_hand.split(&mut shoe);
+        let player_hand2 = player_hand.split(&mut shoe, &rules, 1).unwrap();
         assert!(!player_hand
-            .get_possible_actions()
+            .get_possible_actions(&rules, 2)
             .contains(&PossibleAction(Surrender)));
         assert!(!player_hand2
-            .get_possible_actions()
+            .get_possible_actions(&rules, 2)
             .contains(&PossibleAction(Surrender)));
     }
 
     #[test]
     fn split_possible_action() {
-        let mut shoe: Box<dyn Shoe> = CustomShoe::new(vec![2, 2, 8, 8, 8, 8]);
-        let mut player_hand = test_player_hand(&mut shoe, 40.0);
+        let mut shoe: Box<dyn Shoe> = Box::new("8 8 8 8 2 2".parse::<CustomShoe>().unwrap());
+        let rules = TableRules::default();
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(40));
         assert!(player_hand
-            .get_possible_actions()
+            .get_possible_actions(&rules, 1)
             .contains(&PossibleAction(Split)));
 
-        let player_hand2 = player_hand.split(&mut shoe);
+        let player_hand2 = player_hand.split(&mut shoe, &rules, 1).unwrap();
         assert!(player_hand
-            .get_possible_actions()
+            .get_possible_actions(&rules, 2)
             .contains(&PossibleAction(Split)));
         assert!(player_hand2
-            .get_possible_actions()
+            .get_possible_actions(&rules, 2)
             .contains(&PossibleAction(Split)));
 
-        let player_hand3 = player_hand.split(&mut shoe);
+        let player_hand3 = player_hand.split(&mut shoe, &rules, 2).unwrap();
         assert!(!player_hand
-            .get_possible_actions()
+            .get_possible_actions(&rules, 3)
             .contains(&PossibleAction(Split)));
         assert!(!player_hand2
-            .get_possible_actions()
+            .get_possible_actions(&rules, 3)
             .contains(&PossibleAction(Split)));
         assert!(!player_hand3
-            .get_possible_actions()
+            .get_possible_actions(&rules, 3)
+            .contains(&PossibleAction(Split)));
+    }
+
+    #[test]
+    fn max_split_hands_possible_action() {
+        let mut shoe: Box<dyn Shoe> = Box::new("8 8 8 8".parse::<CustomShoe>().unwrap());
+        let rules = TableRules {
+            max_split_hands: 2,
+            ..TableRules::default()
+        };
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(10));
+        let player_hand2 = player_hand.split(&mut shoe, &rules, 1).unwrap();
+
+        assert!(!player_hand
+            .get_possible_actions(&rules, 2)
             .contains(&PossibleAction(Split)));
+        assert!(!player_hand2
+            .get_possible_actions(&rules, 2)
+            .contains(&PossibleAction(Split)));
+    }
+
+    #[test]
+    fn split_aces_possible_action() {
+        let mut shoe: Box<dyn Shoe> = Box::new("A A A A".parse::<CustomShoe>().unwrap());
+        let rules = TableRules {
+            double_after_split: false,
+            surrender: SurrenderRule::None,
+            ..TableRules::default()
+        };
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(10));
+        assert!(!player_hand
+            .get_possible_actions(&rules, 1)
+            .contains(&PossibleAction(Surrender)));
+
+        let player_hand2 = player_hand.split(&mut shoe, &rules, 1).unwrap();
+        assert!(!player_hand
+            .get_possible_actions(&rules, 2)
+            .iter()
+            .any(|x| matches!(x.action(), Double(_))));
+        assert!(!player_hand2
+            .get_possible_actions(&rules, 2)
+            .contains(&PossibleAction(Split)));
+    }
+
+    #[test]
+    fn insure_up_to_half_bet() {
+        let mut shoe: Box<dyn Shoe> = Box::new("8 8 8 8".parse::<CustomShoe>().unwrap());
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(40));
+        assert_eq!(player_hand.max_insurance(), Money::from_dollars(20));
+
+        player_hand.insure(Money::from_dollars(50));
+        assert_eq!(player_hand.insurance, Money::from_dollars(20));
+        assert_eq!(player_hand.balance(), Money::from_dollars(80));
+
+        let player_hand = test_player_hand(&mut shoe, Money::from_cents(125));
+        assert_eq!(player_hand.max_insurance(), Money::from_cents(62));
+    }
+
+    #[test]
+    fn even_money_is_full_insurance() {
+        let mut shoe: Box<dyn Shoe> = Box::new("A 10".parse::<CustomShoe>().unwrap());
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(40));
+
+        player_hand.insure(Money::from_dollars(1));
+        assert_eq!(player_hand.insurance, Money::from_dollars(20));
+        assert_eq!(player_hand.balance(), Money::from_dollars(80));
+    }
+
+    #[test]
+    fn early_surrender_refunds_half_bet() {
+        let mut shoe: Box<dyn Shoe> = Box::new("10 6 A 10".parse::<CustomShoe>().unwrap());
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(40));
+        assert!(player_hand.can_surrender_early());
+
+        player_hand.surrender(&TableRules::default());
+        assert_eq!(player_hand.hand.status, HandStatus::Surrender);
+        assert_eq!(player_hand.balance(), Money::from_dollars(120));
+        assert_eq!(player_hand.max_insurance(), Money::ZERO);
+
+        let blackjack = test_player_hand(&mut shoe, Money::from_dollars(40));
+        assert!(!blackjack.can_surrender_early());
+    }
+
+    #[test]
+    fn split_aces_receive_one_card() {
+        let mut shoe: Box<dyn Shoe> = Box::new("A A 5 10".parse::<CustomShoe>().unwrap());
+        let rules = TableRules::default();
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(10));
+
+        let player_hand2 = player_hand.split(&mut shoe, &rules, 1).unwrap();
+        assert_eq!(
+            player_hand
+                .hand
+                .cards
+                .iter()
+                .map(Card::value)
+                .collect::<Vec<u8>>(),
+            vec![1, 5]
+        );
+        assert_eq!(player_hand.hand.status, HandStatus::Stood);
+        assert_eq!(
+            player_hand2
+                .hand
+                .cards
+                .iter()
+                .map(Card::value)
+                .collect::<Vec<u8>>(),
+            vec![1, 10]
+        );
+        assert_eq!(player_hand2.hand.status, HandStatus::Stood);
+        assert_eq!(player_hand2.hand.value, 21);
+    }
+
+    #[test]
+    fn hit_split_aces() {
+        let mut shoe: Box<dyn Shoe> = Box::new("A A 5 10".parse::<CustomShoe>().unwrap());
+        let rules = TableRules {
+            hit_split_aces: true,
+            ..TableRules::default()
+        };
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(10));
+
+        let player_hand2 = player_hand.split(&mut shoe, &rules, 1).unwrap();
+        assert_eq!(player_hand.hand.status, HandStatus::Value);
+        assert!(player_hand
+            .get_possible_actions(&rules, 2)
+            .contains(&PossibleAction(Hit)));
+        assert_eq!(player_hand2.hand.status, HandStatus::Stood);
+    }
+
+    #[test]
+    fn resplit_aces() {
+        let mut shoe: Box<dyn Shoe> = Box::new("A A A 5".parse::<CustomShoe>().unwrap());
+        let rules = TableRules {
+            resplit_aces: true,
+            ..TableRules::default()
+        };
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(10));
+
+        let player_hand2 = player_hand.split(&mut shoe, &rules, 1).unwrap();
+        assert_eq!(player_hand.hand.status, HandStatus::Value);
+        assert_eq!(
+            player_hand.get_possible_actions(&rules, 2),
+            vec![PossibleAction(Stand), PossibleAction(Split)]
+        );
+        assert_eq!(player_hand2.hand.status, HandStatus::Stood);
+    }
+
+    #[test]
+    fn split_ten_valued_pair() {
+        let mut shoe: Box<dyn Shoe> = Box::new("K Q".parse::<CustomShoe>().unwrap());
+        let player_hand = test_player_hand(&mut shoe, Money::from_dollars(10));
+
+        let rules = TableRules::default();
+        assert!(player_hand
+            .get_possible_actions(&rules, 1)
+            .contains(&PossibleAction(Split)));
+
+        let rules = TableRules {
+            split_pairs: PairRule::SameRank,
+            ..TableRules::default()
+        };
+        assert!(!player_hand
+            .get_possible_actions(&rules, 1)
+            .contains(&PossibleAction(Split)));
+    }
+
+    #[test]
+    fn double_stands_after_one_card() {
+        let mut shoe: Box<dyn Shoe> = Box::new("5 4 2".parse::<CustomShoe>().unwrap());
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(10));
+
+        player_hand
+            .double(&mut shoe, Money::from_dollars(10))
+            .unwrap();
+        assert_eq!(player_hand.hand.value, 11);
+        assert_eq!(player_hand.hand.status, HandStatus::Stood);
+        assert_eq!(player_hand.bet_amount, Money::from_dollars(20));
+    }
+
+    #[test]
+    fn double_restricted_to_two_cards_and_totals() {
+        let mut shoe: Box<dyn Shoe> = Box::new("5 4 2".parse::<CustomShoe>().unwrap());
+        let rules = TableRules {
+            double: DoubleRule::TenToEleven,
+            ..TableRules::default()
+        };
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(10));
+        assert!(!player_hand
+            .get_possible_actions(&rules, 1)
+            .iter()
+            .any(|x| matches!(x.action(), Double(_))));
+
+        player_hand.hand.deal_card(&mut shoe).unwrap();
+        assert_eq!(player_hand.hand.value, 11);
+        assert!(!player_hand
+            .get_possible_actions(&rules, 1)
+            .iter()
+            .any(|x| matches!(x.action(), Double(_))));
+    }
+
+    #[test]
+    fn double_for_less() {
+        let mut shoe: Box<dyn Shoe> = Box::new("5 4 2".parse::<CustomShoe>().unwrap());
+        let rules = TableRules::default();
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(80));
+        *player_hand.player_balance.borrow_mut() = Money::from_dollars(30);
+
+        let possible_actions = player_hand.get_possible_actions(&rules, 1);
+        assert!(possible_actions.contains(&PossibleAction(Double(Money::from_dollars(30)))));
+
+        player_hand
+            .take_action(Double(Money::from_dollars(50)), &mut shoe, &rules, 1)
+            .unwrap();
+        assert_eq!(player_hand.bet_amount, Money::from_dollars(110));
+        assert_eq!(player_hand.balance(), Money::ZERO);
+        assert_eq!(player_hand.hand.status, HandStatus::Stood);
+    }
+
+    #[test]
+    fn take_action_exhausted_shoe() {
+        let mut shoe: Box<dyn Shoe> = Box::new("8 8".parse::<CustomShoe>().unwrap());
+        let rules = TableRules::default();
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(10));
+        assert_eq!(
+            player_hand.take_action(Hit, &mut shoe, &rules, 1).err(),
+            Some(ShoeError::Exhausted)
+        );
+        assert_eq!(player_hand.hand.cards.len(), 2);
+    }
+
+    #[test]
+    fn exhausted_shoe_leaves_hand_unchanged() {
+        let rules = TableRules::default();
+        let mut shoe: Box<dyn Shoe> = Box::new("8 8 5".parse::<CustomShoe>().unwrap());
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(10));
+        assert_eq!(
+            player_hand.take_action(Split, &mut shoe, &rules, 1).err(),
+            Some(ShoeError::Exhausted)
+        );
+        assert_eq!(player_hand.hand.cards.len(), 2);
+        assert!(!player_hand.split);
+        assert_eq!(player_hand.bet_amount, Money::from_dollars(10));
+        assert_eq!(player_hand.balance(), Money::from_dollars(100));
+        assert!(player_hand
+            .get_possible_actions(&rules, 1)
+            .contains(&PossibleAction(Split)));
+
+        let mut shoe: Box<dyn Shoe> = Box::new("6 5".parse::<CustomShoe>().unwrap());
+        let mut player_hand = test_player_hand(&mut shoe, Money::from_dollars(10));
+        assert_eq!(
+            player_hand
+                .take_action(Double(Money::from_dollars(10)), &mut shoe, &rules, 1)
+                .err(),
+            Some(ShoeError::Exhausted)
+        );
+        assert_eq!(player_hand.hand.cards.len(), 2);
+        assert_eq!(player_hand.bet_amount, Money::from_dollars(10));
+        assert_eq!(player_hand.balance(), Money::from_dollars(100));
+        assert!(player_hand.ledger.borrow().entries().is_empty());
+    }
+
+    #[test]
+    fn no_late_surrender_without_hole_card() {
+        let mut shoe: Box<dyn Shoe> = Box::new("10 6".parse::<CustomShoe>().unwrap());
+        let rules = TableRules {
+            hole_card: HoleCardRule::OriginalBetsOnly,
+            ..TableRules::default()
+        };
+        let player_hand = test_player_hand(&mut shoe, Money::from_dollars(10));
+        assert!(player_hand
+            .get_possible_actions(&TableRules::default(), 1)
+            .contains(&PossibleAction(Surrender)));
+        assert!(!player_hand
+            .get_possible_actions(&rules, 1)
+            .contains(&PossibleAction(Surrender)));
     }
 }
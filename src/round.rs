@@ -1,6 +1,6 @@
 use crate::player_hand::PlayerHand;
-use crate::Hand;
-use crate::HandStatus::{Blackjack, Bust, Lose, Push, Stood, Value, Win};
+use crate::HandStatus::{Blackjack, Bust, Lose, Push, Stood, Surrender, Value, Win};
+use crate::{Card, Hand, HoleCardRule, Money, Reason, TableRules};
 
 pub struct Round {
     pub player_hands: Vec<PlayerHand>,
@@ -9,21 +9,76 @@ pub struct Round {
 }
 
 impl Round {
-    pub(crate) fn start(&mut self) {
+    pub(crate) fn start(&mut self, rules: &TableRules) {
+        if rules.hole_card == HoleCardRule::Peek {
+            self.settle_blackjacks(rules);
+        }
+    }
+
+    fn settle_blackjacks(&mut self, rules: &TableRules) {
         for player_hand in &mut self.player_hands {
+            if self.dealer.status == Blackjack {
+                player_hand.transfer(player_hand.insurance * 3, Reason::InsurancePayout);
+            }
+
             match (self.dealer.status, player_hand.hand.status) {
                 (Blackjack, Blackjack) => {
-                    *player_hand.player_balance.borrow_mut() += player_hand.bet_amount;
+                    player_hand.transfer(player_hand.bet_amount, Reason::Payout);
                     player_hand.hand.status = Push;
                 }
+                // busted hands were lost before the dealer's hand was seen
+                (Blackjack, Bust | Surrender) => {}
                 (Blackjack, _) => player_hand.hand.status = Lose,
-                (_, Blackjack) => {
-                    *player_hand.player_balance.borrow_mut() += player_hand.bet_amount * 5.0 / 2.0
-                }
+                (_, Blackjack) => player_hand.transfer(
+                    player_hand.bet_amount
+                        + player_hand
+                            .bet_amount
+                            .scale(rules.blackjack_payout, rules.rounding),
+                    Reason::Payout,
+                ),
                 (_, _) => {}
             }
         }
+
+        if self.dealer.status == Blackjack && rules.hole_card == HoleCardRule::OriginalBetsOnly {
+            // a busted hand has already lost its seat's original bet
+            let mut seats: Vec<usize> = self
+                .player_hands
+                .iter()
+                .filter(|player_hand| player_hand.hand.status == Bust)
+                .map(|player_hand| player_hand.seat)
+                .collect();
+            for player_hand in &mut self.player_hands {
+                if player_hand.hand.status != Lose {
+                    continue;
+                }
+                let mut refund = player_hand.bet_amount - player_hand.original_bet;
+                if seats.contains(&player_hand.seat) {
+                    refund += player_hand.original_bet;
+                } else {
+                    seats.push(player_hand.seat);
+                }
+                player_hand.transfer(refund, Reason::Payout);
+            }
+        }
+    }
+
+    pub fn discards(&self) -> Vec<Card> {
+        self.player_hands
+            .iter()
+            .flat_map(|player_hand| player_hand.hand.cards.iter())
+            .chain(self.dealer.cards.iter())
+            .copied()
+            .collect()
     }
+
+    pub(crate) fn seat_hands(&self, seat: usize) -> usize {
+        self.player_hands
+            .iter()
+            .filter(|player_hand| player_hand.seat == seat)
+            .count()
+    }
+
     pub(crate) fn update_active_hand_index(&mut self) -> bool {
         if self.active_hand_index == self.player_hands.len() {
             false
@@ -37,7 +92,14 @@ impl Round {
         }
     }
 
-    pub(crate) fn end(&mut self) {
+    pub(crate) fn end(&mut self, rules: &TableRules) {
+        if rules.hole_card != HoleCardRule::Peek {
+            self.settle_blackjacks(rules);
+            if self.dealer.status == Blackjack {
+                return;
+            }
+        }
+
         for player_hand in &mut self.player_hands {
             if let Stood = player_hand.hand.status {
                 player_hand.hand.status = if self.dealer.status != Bust {
@@ -51,11 +113,12 @@ impl Round {
                 }
             }
 
-            *player_hand.player_balance.borrow_mut() += match player_hand.hand.status {
-                Win => player_hand.bet_amount * 2.0,
+            let payout = match player_hand.hand.status {
+                Win => player_hand.bet_amount * 2,
                 Push => player_hand.bet_amount,
-                _ => 0.0,
+                _ => Money::ZERO,
             };
+            player_hand.transfer(payout, Reason::Payout);
         }
     }
 }
@@ -65,62 +128,149 @@ mod round_tests {
     use crate::player_hand::PlayerHand;
     use crate::round::Round;
     use crate::shoe::{CustomShoe, Shoe};
-    use crate::Hand;
-    use crate::HandStatus::{Blackjack, Lose, Push, Stood, Value, Win};
+    use crate::HandStatus::{Blackjack, Bust, Lose, Push, Stood, Surrender, Value, Win};
+    use crate::{Hand, HoleCardRule, Ledger, Money, PlayerId, Rounding, TableRules};
     use std::cell::RefCell;
     use std::rc::Rc;
 
     fn test_round(shoe: &mut Box<dyn Shoe>) -> Round {
+        let ledger = Rc::new(RefCell::new(Ledger::new()));
         Round {
             player_hands: vec![
                 PlayerHand {
-                    hand: Hand::new(shoe),
-                    bet_amount: 4.0,
-                    player_balance: Rc::new(RefCell::new(10.0)),
+                    hand: Hand::new(shoe).unwrap(),
+                    bet_amount: Money::from_dollars(4),
+                    insurance: Money::ZERO,
+                    original_bet: Money::from_dollars(4),
+                    player_balance: Rc::new(RefCell::new(Money::from_dollars(10))),
+                    ledger: ledger.clone(),
                     split: false,
+                    seat: 0,
+                    hand_index: 0,
+                    player: PlayerId(0),
                 },
                 PlayerHand {
-                    hand: Hand::new(shoe),
-                    bet_amount: 4.0,
-                    player_balance: Rc::new(RefCell::new(10.0)),
+                    hand: Hand::new(shoe).unwrap(),
+                    bet_amount: Money::from_dollars(4),
+                    insurance: Money::ZERO,
+                    original_bet: Money::from_dollars(4),
+                    player_balance: Rc::new(RefCell::new(Money::from_dollars(10))),
+                    ledger: ledger.clone(),
                     split: false,
+                    seat: 1,
+                    hand_index: 0,
+                    player: PlayerId(0),
                 },
             ],
             active_hand_index: 0,
-            dealer: Hand::new(shoe),
+            dealer: Hand::new(shoe).unwrap(),
         }
     }
 
     #[test]
     fn test_round_start_dealer_blackjack() {
-        let mut shoe: Box<dyn Shoe> = CustomShoe::new(vec![10, 1, 1, 10, 8, 8]);
+        let mut shoe: Box<dyn Shoe> = Box::new("8 8 10 A A 10".parse::<CustomShoe>().unwrap());
+        let mut round = test_round(&mut shoe);
+        round.start(&TableRules::default());
+        assert_eq!(Lose, round.player_hands[0].hand.status);
+        assert_eq!(
+            Money::from_dollars(10),
+            *round.player_hands[0].player_balance.borrow()
+        );
+
+        assert_eq!(Push, round.player_hands[1].hand.status);
+        assert_eq!(
+            Money::from_dollars(14),
+            *round.player_hands[1].player_balance.borrow()
+        );
+    }
+
+    #[test]
+    fn test_round_start_dealer_blackjack_insurance() {
+        let mut shoe: Box<dyn Shoe> = Box::new("8 8 10 A 10 A".parse::<CustomShoe>().unwrap());
         let mut round = test_round(&mut shoe);
-        round.start();
+        round.player_hands[0].insure(Money::from_dollars(2));
+        round.player_hands[1].insure(Money::from_dollars(2));
+        round.start(&TableRules::default());
+
         assert_eq!(Lose, round.player_hands[0].hand.status);
-        assert!(*round.player_hands[0].player_balance.borrow() < 10.01);
+        assert_eq!(
+            Money::from_dollars(14),
+            *round.player_hands[0].player_balance.borrow()
+        );
 
         assert_eq!(Push, round.player_hands[1].hand.status);
-        assert!(*round.player_hands[1].player_balance.borrow() > 13.99);
+        assert_eq!(
+            Money::from_dollars(18),
+            *round.player_hands[1].player_balance.borrow()
+        );
+    }
+
+    #[test]
+    fn test_round_start_no_dealer_blackjack_insurance() {
+        let mut shoe: Box<dyn Shoe> = Box::new("8 8 10 A A 8".parse::<CustomShoe>().unwrap());
+        let mut round = test_round(&mut shoe);
+        round.player_hands[0].insure(Money::from_dollars(2));
+        round.player_hands[1].insure(Money::from_dollars(2));
+        round.start(&TableRules::default());
+
+        assert_eq!(Value, round.player_hands[0].hand.status);
+        assert_eq!(
+            Money::from_dollars(8),
+            *round.player_hands[0].player_balance.borrow()
+        );
+
+        assert_eq!(Blackjack, round.player_hands[1].hand.status);
+        assert_eq!(
+            Money::from_dollars(18),
+            *round.player_hands[1].player_balance.borrow()
+        );
+    }
+
+    #[test]
+    fn test_round_start_dealer_blackjack_early_surrender() {
+        let mut shoe: Box<dyn Shoe> = Box::new("8 8 8 8 10 A".parse::<CustomShoe>().unwrap());
+        let mut round = test_round(&mut shoe);
+        round.player_hands[0].surrender(&TableRules::default());
+        round.start(&TableRules::default());
+
+        assert_eq!(Surrender, round.player_hands[0].hand.status);
+        assert_eq!(
+            Money::from_dollars(12),
+            *round.player_hands[0].player_balance.borrow()
+        );
+
+        assert_eq!(Lose, round.player_hands[1].hand.status);
+        assert_eq!(
+            Money::from_dollars(10),
+            *round.player_hands[1].player_balance.borrow()
+        );
     }
 
     #[test]
     fn test_round_start_no_dealer_blackjack() {
-        let mut shoe: Box<dyn Shoe> = CustomShoe::new(vec![8, 8, 1, 10, 8, 8]);
+        let mut shoe: Box<dyn Shoe> = Box::new("8 8 10 A 8 8".parse::<CustomShoe>().unwrap());
         let mut round = test_round(&mut shoe);
-        round.start();
+        round.start(&TableRules::default());
 
         assert_eq!(Value, round.player_hands[0].hand.status);
-        assert!(*round.player_hands[0].player_balance.borrow() < 10.01);
+        assert_eq!(
+            Money::from_dollars(10),
+            *round.player_hands[0].player_balance.borrow()
+        );
 
         assert_eq!(Blackjack, round.player_hands[1].hand.status);
-        assert!(*round.player_hands[1].player_balance.borrow() > 19.99);
+        assert_eq!(
+            Money::from_dollars(20),
+            *round.player_hands[1].player_balance.borrow()
+        );
     }
 
     #[test]
     fn test_round_update_second_active() {
-        let mut shoe: Box<dyn Shoe> = CustomShoe::new(vec![7, 8, 8, 8, 8, 7, 7]);
+        let mut shoe: Box<dyn Shoe> = Box::new("7 7 8 8 8 8 7".parse::<CustomShoe>().unwrap());
         let mut round = test_round(&mut shoe);
-        round.player_hands[0].hand.deal_card(&mut shoe);
+        round.player_hands[0].hand.deal_card(&mut shoe).unwrap();
 
         assert!(round.update_active_hand_index());
         assert_eq!(round.active_hand_index, 1);
@@ -131,33 +281,193 @@ mod round_tests {
 
     #[test]
     fn test_round_end() {
-        let mut shoe: Box<dyn Shoe> = CustomShoe::new(vec![1, 5, 8, 10, 8, 8, 7, 7]);
+        let mut shoe: Box<dyn Shoe> = Box::new("7 7 8 8 10 8 5 A".parse::<CustomShoe>().unwrap());
         let mut round = test_round(&mut shoe);
-        round.player_hands[0].hand.deal_card(&mut shoe);
-        round.player_hands[1].hand.deal_card(&mut shoe);
+        round.player_hands[0].hand.deal_card(&mut shoe).unwrap();
+        round.player_hands[1].hand.deal_card(&mut shoe).unwrap();
         round.player_hands[0].hand.status = Stood;
         round.player_hands[1].hand.status = Stood;
 
-        round.end();
+        round.end(&TableRules::default());
 
         assert_eq!(Win, round.player_hands[0].hand.status);
-        assert!(*round.player_hands[0].player_balance.borrow() > 13.99);
+        assert_eq!(
+            Money::from_dollars(18),
+            *round.player_hands[0].player_balance.borrow()
+        );
 
         assert_eq!(Lose, round.player_hands[1].hand.status);
-        assert!(*round.player_hands[1].player_balance.borrow() < 10.01);
+        assert_eq!(
+            Money::from_dollars(10),
+            *round.player_hands[1].player_balance.borrow()
+        );
     }
 
     #[test]
     fn test_dealer_twenty_one() {
-        let mut shoe: Box<dyn Shoe> = CustomShoe::new(vec![5, 6, 10, 10, 10, 10, 10]);
+        let mut shoe: Box<dyn Shoe> = Box::new("10 10 10 10 10 6 5".parse::<CustomShoe>().unwrap());
+        let mut round = test_round(&mut shoe);
+        round.player_hands[0].hand.status = Stood;
+        round.player_hands[1].hand.status = Stood;
+        round
+            .dealer
+            .dealer_turn(&mut shoe, &TableRules::default())
+            .unwrap();
+
+        round.end(&TableRules::default());
+
+        assert_eq!(Lose, round.player_hands[0].hand.status);
+        assert_eq!(Lose, round.player_hands[1].hand.status);
+    }
+
+    #[test]
+    fn test_round_no_hole_card_blackjack_paid_at_end() {
+        let mut shoe: Box<dyn Shoe> = Box::new("10 A 8 8 10 7".parse::<CustomShoe>().unwrap());
+        let rules = TableRules {
+            hole_card: HoleCardRule::OriginalBetsOnly,
+            ..TableRules::default()
+        };
+        let mut round = test_round(&mut shoe);
+        round.start(&rules);
+        assert_eq!(
+            Money::from_dollars(10),
+            *round.player_hands[0].player_balance.borrow()
+        );
+
+        round.player_hands[1].hand.status = Stood;
+        round.end(&rules);
+
+        assert_eq!(Blackjack, round.player_hands[0].hand.status);
+        assert_eq!(
+            Money::from_dollars(20),
+            *round.player_hands[0].player_balance.borrow()
+        );
+        assert_eq!(Lose, round.player_hands[1].hand.status);
+    }
+
+    #[test]
+    fn test_round_no_hole_card_original_bets_only() {
+        let mut shoe: Box<dyn Shoe> = Box::new("8 8 8 8 A 10".parse::<CustomShoe>().unwrap());
+        let rules = TableRules {
+            hole_card: HoleCardRule::OriginalBetsOnly,
+            ..TableRules::default()
+        };
         let mut round = test_round(&mut shoe);
+        round.player_hands[0].bet_amount = Money::from_dollars(8);
+        *round.player_hands[0].player_balance.borrow_mut() = Money::from_dollars(6);
         round.player_hands[0].hand.status = Stood;
+        round.player_hands[1].seat = 0;
+        round.player_hands[1].hand.status = Stood;
+        round.end(&rules);
+
+        assert_eq!(Lose, round.player_hands[0].hand.status);
+        assert_eq!(
+            Money::from_dollars(10),
+            *round.player_hands[0].player_balance.borrow()
+        );
+        assert_eq!(Lose, round.player_hands[1].hand.status);
+        assert_eq!(
+            Money::from_dollars(14),
+            *round.player_hands[1].player_balance.borrow()
+        );
+    }
+
+    #[test]
+    fn test_round_no_hole_card_original_bets_only_bust() {
+        let mut shoe: Box<dyn Shoe> = Box::new("8 8 8 8 A 10".parse::<CustomShoe>().unwrap());
+        let rules = TableRules {
+            hole_card: HoleCardRule::OriginalBetsOnly,
+            ..TableRules::default()
+        };
+        let mut round = test_round(&mut shoe);
+        round.player_hands[0].bet_amount = Money::from_dollars(8);
+        *round.player_hands[0].player_balance.borrow_mut() = Money::from_dollars(2);
+        round.player_hands[0].hand.status = Bust;
         round.player_hands[1].hand.status = Stood;
-        round.dealer.dealer_turn(&mut shoe);
+        round.end(&rules);
 
-        round.end();
+        assert_eq!(Bust, round.player_hands[0].hand.status);
+        assert_eq!(
+            Money::from_dollars(2),
+            *round.player_hands[0].player_balance.borrow()
+        );
+        assert_eq!(Lose, round.player_hands[1].hand.status);
+        assert_eq!(
+            Money::from_dollars(10),
+            *round.player_hands[1].player_balance.borrow()
+        );
+
+        let mut shoe: Box<dyn Shoe> = Box::new("8 8 8 8 A 10".parse::<CustomShoe>().unwrap());
+        let mut round = test_round(&mut shoe);
+        round.player_hands[0].hand.status = Bust;
+        round.player_hands[1].seat = 0;
+        round.player_hands[1].hand.status = Stood;
+        round.end(&rules);
+
+        // the split hand's bet is refunded, the busted hand took the original bet
+        assert_eq!(Bust, round.player_hands[0].hand.status);
+        assert_eq!(
+            Money::from_dollars(14),
+            *round.player_hands[1].player_balance.borrow()
+        );
+    }
+
+    #[test]
+    fn test_round_no_hole_card_lose_all() {
+        let mut shoe: Box<dyn Shoe> = Box::new("8 8 8 8 A 10".parse::<CustomShoe>().unwrap());
+        let rules = TableRules {
+            hole_card: HoleCardRule::LoseAll,
+            ..TableRules::default()
+        };
+        let mut round = test_round(&mut shoe);
+        round.player_hands[0].bet_amount = Money::from_dollars(8);
+        *round.player_hands[0].player_balance.borrow_mut() = Money::from_dollars(6);
+        round.player_hands[0].hand.status = Stood;
+        round.player_hands[1].seat = 0;
+        round.player_hands[1].hand.status = Stood;
+        round.end(&rules);
 
         assert_eq!(Lose, round.player_hands[0].hand.status);
+        assert_eq!(
+            Money::from_dollars(6),
+            *round.player_hands[0].player_balance.borrow()
+        );
         assert_eq!(Lose, round.player_hands[1].hand.status);
+        assert_eq!(
+            Money::from_dollars(10),
+            *round.player_hands[1].player_balance.borrow()
+        );
+    }
+
+    #[test]
+    fn test_round_discards() {
+        let mut shoe: Box<dyn Shoe> = Box::new("8 8 10 A 10 7".parse::<CustomShoe>().unwrap());
+        let round = test_round(&mut shoe);
+        let discards: Vec<u8> = round.discards().iter().map(|card| card.value()).collect();
+        assert_eq!(discards, vec![8, 8, 10, 1, 10, 7]);
+    }
+
+    #[test]
+    fn test_round_blackjack_payout_rounding() {
+        let mut shoe: Box<dyn Shoe> = Box::new("A 10 10 6 10 7".parse::<CustomShoe>().unwrap());
+        let mut round = test_round(&mut shoe);
+        round.player_hands[0].bet_amount = Money::from_cents(125);
+        round.player_hands[1].bet_amount = Money::from_cents(125);
+        round.start(&TableRules::default());
+        // 3:2 on $1.25 is $1.875, rounded down to $1.87
+        assert_eq!(
+            Money::from_cents(1312),
+            *round.player_hands[0].player_balance.borrow()
+        );
+
+        let rules = TableRules {
+            rounding: Rounding::Up,
+            ..TableRules::default()
+        };
+        round.player_hands[1].surrender(&rules);
+        assert_eq!(
+            Money::from_cents(1063),
+            *round.player_hands[1].player_balance.borrow()
+        );
     }
 }
@@ -0,0 +1,185 @@
+use crate::{Money, PlayerBalanceError, Rounding};
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum SurrenderRule {
+    None,
+    /// Only offered when the dealer takes a hole card.
+    Late,
+    /// Surrender is also offered before the dealer checks for blackjack against the selected up cards.
+    Early {
+        against_ten: bool,
+        against_ace: bool,
+    },
+}
+
+impl SurrenderRule {
+    pub(crate) fn early_against(&self, up_card: u8) -> bool {
+        match self {
+            SurrenderRule::Early {
+                against_ten,
+                against_ace,
+            } => (up_card == 10 && *against_ten) || (up_card == 1 && *against_ace),
+            _ => false,
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum DoubleRule {
+    AnyTwoCards,
+    NineToEleven,
+    TenToEleven,
+}
+
+impl DoubleRule {
+    pub(crate) fn allows(&self, value: u8) -> bool {
+        match self {
+            DoubleRule::AnyTwoCards => true,
+            DoubleRule::NineToEleven => (9..=11).contains(&value),
+            DoubleRule::TenToEleven => (10..=11).contains(&value),
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum PairRule {
+    /// Any two cards of the same value can be split, such as a king and a ten.
+    SameValue,
+    /// Only two cards of the same rank can be split.
+    SameRank,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum HoleCardRule {
+    /// The dealer takes a hole card and checks for blackjack before anyone acts.
+    Peek,
+    /// No hole card. A dealer blackjack takes only the original bets, doubles and splits are returned.
+    OriginalBetsOnly,
+    /// No hole card. A dealer blackjack takes every bet on the table, including doubles and splits.
+    LoseAll,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct TableLimits {
+    pub minimum: Money,
+    pub maximum: Money,
+    /// The smallest chip on the table. Every bet must be a multiple of it.
+    pub increment: Money,
+    /// The most a player can have bet across all of their seats.
+    pub maximum_per_player: Money,
+}
+
+impl TableLimits {
+    pub fn check(&self, amount: Money) -> Result<(), PlayerBalanceError> {
+        if amount < self.minimum || amount <= Money::ZERO {
+            Err(PlayerBalanceError::BetBelowMinimum)
+        } else if amount > self.maximum {
+            Err(PlayerBalanceError::BetAboveMaximum)
+        } else if amount.cents() % self.increment.cents().max(1) != 0 {
+            Err(PlayerBalanceError::BetNotMultipleOfIncrement)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Default for TableLimits {
+    fn default() -> Self {
+        TableLimits {
+            minimum: Money::from_dollars(1),
+            maximum: Money::from_dollars(1000),
+            increment: Money::from_dollars(1),
+            maximum_per_player: Money::from_dollars(2000),
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct TableRules {
+    pub dealer_hits_soft_17: bool,
+    pub blackjack_payout: f64,
+    pub double: DoubleRule,
+    pub double_after_split: bool,
+    pub split_pairs: PairRule,
+    pub max_split_hands: usize,
+    pub resplit_aces: bool,
+    pub hit_split_aces: bool,
+    pub surrender: SurrenderRule,
+    pub insurance: bool,
+    pub hole_card: HoleCardRule,
+    /// Applied to blackjack payouts and surrender refunds that come to a fraction of a cent. The
+    /// insurance limit always rounds down.
+    pub rounding: Rounding,
+    pub limits: TableLimits,
+    pub seats: usize,
+}
+
+impl Default for TableRules {
+    fn default() -> Self {
+        TableRules {
+            dealer_hits_soft_17: false,
+            blackjack_payout: 1.5,
+            double: DoubleRule::AnyTwoCards,
+            double_after_split: true,
+            split_pairs: PairRule::SameValue,
+            max_split_hands: 4,
+            resplit_aces: false,
+            hit_split_aces: false,
+            surrender: SurrenderRule::Late,
+            insurance: true,
+            hole_card: HoleCardRule::Peek,
+            rounding: Rounding::Down,
+            limits: TableLimits::default(),
+            seats: 7,
+        }
+    }
+}
+
+#[cfg(test)]
+mod rules_tests {
+    use super::*;
+
+    #[test]
+    fn early_surrender_against_up_card() {
+        let surrender = SurrenderRule::Early {
+            against_ten: true,
+            against_ace: false,
+        };
+        assert!(surrender.early_against(10));
+        assert!(!surrender.early_against(1));
+        assert!(!surrender.early_against(9));
+        assert!(!SurrenderRule::Late.early_against(10));
+    }
+
+    #[test]
+    fn double_rule_allows_totals() {
+        assert!(DoubleRule::AnyTwoCards.allows(17));
+        assert!(DoubleRule::NineToEleven.allows(9));
+        assert!(!DoubleRule::NineToEleven.allows(12));
+        assert!(!DoubleRule::TenToEleven.allows(9));
+        assert!(DoubleRule::TenToEleven.allows(11));
+    }
+
+    #[test]
+    fn table_limits_check() {
+        let limits = TableLimits {
+            minimum: Money::from_dollars(5),
+            maximum: Money::from_dollars(500),
+            increment: Money::from_cents(50),
+            ..TableLimits::default()
+        };
+        assert_eq!(limits.check(Money::from_cents(750)), Ok(()));
+        assert_eq!(
+            limits.check(Money::ZERO),
+            Err(PlayerBalanceError::BetBelowMinimum)
+        );
+        assert_eq!(
+            limits.check(Money::from_dollars(501)),
+            Err(PlayerBalanceError::BetAboveMaximum)
+        );
+        assert_eq!(
+            limits.check(Money::from_cents(725)),
+            Err(PlayerBalanceError::BetNotMultipleOfIncrement)
+        );
+    }
+}
@@ -0,0 +1,23 @@
+use crate::{Money, Player};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Identifies a player who has joined the table. Ids aren't reused after a player leaves.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct PlayerId(pub(crate) usize);
+
+pub(crate) struct Account {
+    pub(crate) name: String,
+    pub(crate) balance: Rc<RefCell<Money>>,
+    /// Players without one are driven through `Game::apply`.
+    pub(crate) player: Option<Box<dyn Player>>,
+    pub(crate) sitting_out: bool,
+}
+
+/// A betting position at the table, taken by a player.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub(crate) struct Seat {
+    pub(crate) player: PlayerId,
+    /// The bet for the next round. Zero if the seat isn't played.
+    pub(crate) bet: Money,
+}
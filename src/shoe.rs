@@ -1,62 +1,394 @@
+use crate::{Card, ParseCardError, Rank, Suit};
 use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+
+use crate::shuffle::random_index;
+pub use crate::shuffle::ShuffleModel;
+
+#[derive(PartialEq, Debug)]
+pub enum ShoeError {
+    Exhausted,
+}
 
 pub trait Shoe {
-    fn deal(&mut self) -> u8;
-    fn new_round(&mut self) {}
+    fn deal(&mut self) -> Result<Card, ShoeError>;
+    fn new_round(&mut self, _discards: Vec<Card>) {}
+    fn reshuffled(&self) -> bool {
+        false
+    }
+
+    /// The number of cards left in the shoe. Burned cards are not included.
+    fn remaining(&self) -> usize;
+    fn remaining_by_rank(&self, rank: Rank) -> usize;
+    /// The number of cards that have left the shoe since it was last shuffled, including burned
+    /// cards.
+    fn dealt(&self) -> usize;
+    /// Shuffles every card back into the shoe. Only call it between rounds, while no cards are on
+    /// the table. Shoes that can't be reshuffled ignore it.
+    fn reshuffle(&mut self) {}
+    fn decks_remaining(&self) -> f64 {
+        self.remaining() as f64 / 52.0
+    }
+}
+
+fn count_rank<'a>(cards: impl IntoIterator<Item = &'a Card>, rank: Rank) -> usize {
+    cards.into_iter().filter(|card| card.rank == rank).count()
+}
+
+fn new_deck(size: u8) -> Vec<Card> {
+    let mut deck = Vec::new();
+    (0..size).for_each(|_| {
+        for suit in Suit::ALL {
+            deck.extend(Rank::ALL.map(|rank| Card::new(rank, suit)));
+        }
+    });
+    deck
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Penetration {
+    /// The fraction of the shoe dealt before the cut card comes out.
+    Fraction(f64),
+    /// The number of cards dealt before the cut card comes out.
+    Cards(usize),
 }
 
 pub struct StandardShoe {
-    deck: Vec<u8>,
+    deck: Vec<Card>,
     size: u8,
+    penetration: Penetration,
+    reshuffled: bool,
+    burn_cards: usize,
+    discards: Vec<Card>,
+    procedure: Vec<ShuffleModel>,
+    seed: u64,
+    rng: ChaCha8Rng,
 }
 
 impl StandardShoe {
     pub fn new(size: u8) -> Box<StandardShoe> {
-        Box::new(Self::create_shoe(size))
+        Self::with_penetration(size, Penetration::Fraction(0.75))
     }
 
-    fn create_shoe(size: u8) -> StandardShoe {
-        let mut deck = Vec::new();
-        (0..size * 4).for_each(|_| {
-            deck.extend([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 10, 10, 10]);
-        });
-        deck.shuffle(&mut rand::thread_rng());
+    pub fn with_penetration(size: u8, penetration: Penetration) -> Box<StandardShoe> {
+        Self::with_seed(size, penetration, rand::thread_rng().gen())
+    }
 
-        StandardShoe { deck, size }
+    pub fn with_seed(size: u8, penetration: Penetration, seed: u64) -> Box<StandardShoe> {
+        let mut shoe = StandardShoe {
+            deck: Vec::new(),
+            size,
+            penetration,
+            reshuffled: false,
+            burn_cards: 0,
+            discards: Vec::new(),
+            procedure: vec![ShuffleModel::Uniform],
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        };
+        shoe.shuffle();
+        Box::new(shoe)
+    }
+
+    /// Burns cards from the top of the shoe after every shuffle, starting with the current shoe.
+    pub fn with_burn_cards(mut self: Box<Self>, burn_cards: usize) -> Box<StandardShoe> {
+        self.burn_cards = burn_cards;
+        self.burn();
+        self
+    }
+
+    /// Shuffles with the given procedure from the next shuffle onwards. The discards from each
+    /// round and the cards left behind the cut card are gathered and shuffled in order, so the
+    /// procedure decides how much of the previous shoe's order survives.
+    pub fn with_shuffle(mut self: Box<Self>, procedure: Vec<ShuffleModel>) -> Box<StandardShoe> {
+        self.procedure = procedure;
+        self
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn gather(&mut self) -> Vec<Card> {
+        let mut counts = HashMap::<Card, u8>::new();
+        let mut cards = Vec::new();
+        for card in self.discards.drain(..).chain(self.deck.drain(..)) {
+            let count = counts.entry(card).or_insert(0);
+            if *count < self.size {
+                *count += 1;
+                cards.push(card);
+            }
+        }
+
+        // cards that never came back to the discard tray are replaced
+        for card in new_deck(self.size) {
+            match counts.get_mut(&card) {
+                Some(count) if *count > 0 => *count -= 1,
+                _ => cards.push(card),
+            }
+        }
+        cards
+    }
+
+    fn burn(&mut self) {
+        let remaining = self.deck.len().saturating_sub(self.burn_cards);
+        self.deck.truncate(remaining);
+    }
+
+    fn cut_card(&self) -> usize {
+        let shoe_size = self.size as usize * 52;
+        match self.penetration {
+            Penetration::Fraction(x) => (shoe_size as f64 * x).round() as usize,
+            Penetration::Cards(x) => x,
+        }
+        .min(shoe_size)
+    }
+
+    fn shuffle(&mut self) {
+        let cards = self.gather();
+        self.shuffle_cards(cards);
+    }
+
+    fn shuffle_cards(&mut self, cards: Vec<Card>) {
+        self.deck = self
+            .procedure
+            .iter()
+            .fold(cards, |cards, model| model.apply(cards, &mut self.rng));
+        self.burn();
+        self.reshuffled = true;
     }
 }
 
 impl Shoe for StandardShoe {
-    fn deal(&mut self) -> u8 {
-        match self.deck.pop() {
-            None => {
-                self.new_round();
-                self.deal()
-            }
-            Some(x) => x,
+    fn deal(&mut self) -> Result<Card, ShoeError> {
+        if self.deck.is_empty() && !self.discards.is_empty() {
+            // out of cards in the middle of a round, so only the discards are shuffled back in.
+            // The cards on the table join them at the next shuffle.
+            let discards = std::mem::take(&mut self.discards);
+            self.shuffle_cards(discards);
         }
+        self.deck.pop().ok_or(ShoeError::Exhausted)
     }
 
-    fn new_round(&mut self) {
-        if self.deck.len() < 20 {
-            *self = Self::create_shoe(self.size);
+    fn new_round(&mut self, discards: Vec<Card>) {
+        self.discards.extend(discards);
+        self.reshuffled = false;
+        if self.deck.is_empty() || self.dealt() >= self.cut_card() {
+            self.shuffle();
         }
     }
+
+    fn reshuffled(&self) -> bool {
+        self.reshuffled
+    }
+
+    fn remaining(&self) -> usize {
+        self.deck.len()
+    }
+
+    fn remaining_by_rank(&self, rank: Rank) -> usize {
+        count_rank(&self.deck, rank)
+    }
+
+    fn dealt(&self) -> usize {
+        self.size as usize * 52 - self.deck.len()
+    }
+
+    fn reshuffle(&mut self) {
+        self.shuffle();
+    }
 }
 
-pub(crate) struct CustomShoe {
-    pub(crate) deck: Vec<u8>,
+pub struct CustomShoe {
+    deck: VecDeque<Card>,
+    dealt: usize,
+    fallback: Option<Box<dyn Shoe>>,
 }
 
 impl CustomShoe {
-    pub fn new(deck: Vec<u8>) -> Box<CustomShoe> {
-        Box::new(CustomShoe { deck })
+    pub fn new(deck: Vec<Card>) -> Box<CustomShoe> {
+        Box::new(CustomShoe {
+            deck: deck.into(),
+            dealt: 0,
+            fallback: None,
+        })
+    }
+
+    pub fn with_fallback(deck: Vec<Card>, fallback: Box<dyn Shoe>) -> Box<CustomShoe> {
+        Box::new(CustomShoe {
+            deck: deck.into(),
+            dealt: 0,
+            fallback: Some(fallback),
+        })
+    }
+}
+
+impl FromStr for CustomShoe {
+    type Err = ParseCardError;
+
+    /// Parses a whitespace separated list of cards such as "A 10 8S 8h 6". Cards given without a
+    /// suit are dealt as spades.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let deck = s
+            .split_whitespace()
+            .map(|card| match card.parse::<Card>() {
+                Ok(card) => Ok(card),
+                Err(e) => match card.parse::<Rank>() {
+                    Ok(rank) => Ok(Card::new(rank, Suit::Spades)),
+                    Err(_) => Err(e),
+                },
+            })
+            .collect::<Result<VecDeque<Card>, ParseCardError>>()?;
+
+        Ok(CustomShoe {
+            deck,
+            dealt: 0,
+            fallback: None,
+        })
     }
 }
 
 impl Shoe for CustomShoe {
-    fn deal(&mut self) -> u8 {
-        self.deck.pop().unwrap()
+    fn deal(&mut self) -> Result<Card, ShoeError> {
+        let card = match (self.deck.pop_front(), &mut self.fallback) {
+            (Some(x), _) => x,
+            (None, Some(fallback)) => fallback.deal()?,
+            (None, None) => return Err(ShoeError::Exhausted),
+        };
+        self.dealt += 1;
+        Ok(card)
+    }
+
+    fn new_round(&mut self, discards: Vec<Card>) {
+        if let Some(fallback) = &mut self.fallback {
+            fallback.new_round(discards)
+        }
+    }
+
+    fn reshuffled(&self) -> bool {
+        match &self.fallback {
+            Some(fallback) if self.deck.is_empty() => fallback.reshuffled(),
+            _ => false,
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.deck.len() + self.fallback.as_ref().map_or(0, |x| x.remaining())
+    }
+
+    fn remaining_by_rank(&self, rank: Rank) -> usize {
+        count_rank(&self.deck, rank)
+            + self
+                .fallback
+                .as_ref()
+                .map_or(0, |x| x.remaining_by_rank(rank))
+    }
+
+    fn dealt(&self) -> usize {
+        self.dealt
+    }
+
+    fn reshuffle(&mut self) {
+        if let Some(fallback) = &mut self.fallback {
+            fallback.reshuffle()
+        }
+    }
+}
+
+pub struct ContinuousShuffleShoe {
+    deck: Vec<Card>,
+    size: u8,
+    held: VecDeque<Vec<Card>>,
+    loading: Vec<Card>,
+    delay: usize,
+    batch_size: usize,
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl ContinuousShuffleShoe {
+    /// Discards are held out of the machine for `delay` rounds and are then loaded back in once at
+    /// least `batch_size` cards are waiting.
+    pub fn new(size: u8, delay: usize, batch_size: usize) -> Box<ContinuousShuffleShoe> {
+        Self::with_seed(size, delay, batch_size, rand::thread_rng().gen())
+    }
+
+    pub fn with_seed(
+        size: u8,
+        delay: usize,
+        batch_size: usize,
+        seed: u64,
+    ) -> Box<ContinuousShuffleShoe> {
+        let mut deck = new_deck(size);
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        deck.shuffle(&mut rng);
+
+        Box::new(ContinuousShuffleShoe {
+            deck,
+            size,
+            held: VecDeque::new(),
+            loading: Vec::new(),
+            delay,
+            batch_size,
+            seed,
+            rng,
+        })
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn load(&mut self) {
+        for card in self.loading.drain(..) {
+            let index = random_index(&mut self.rng, self.deck.len() + 1);
+            self.deck.insert(index, card);
+        }
+    }
+}
+
+impl Shoe for ContinuousShuffleShoe {
+    fn deal(&mut self) -> Result<Card, ShoeError> {
+        match self.deck.pop() {
+            None => {
+                self.loading.extend(self.held.drain(..).flatten());
+                self.load();
+                self.deck.pop().ok_or(ShoeError::Exhausted)
+            }
+            Some(x) => Ok(x),
+        }
+    }
+
+    fn new_round(&mut self, discards: Vec<Card>) {
+        self.held.push_back(discards);
+        while self.held.len() > self.delay {
+            let batch = self.held.pop_front().unwrap_or_default();
+            self.loading.extend(batch);
+        }
+
+        if !self.loading.is_empty() && self.loading.len() >= self.batch_size {
+            self.load();
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.deck.len()
+    }
+
+    fn remaining_by_rank(&self, rank: Rank) -> usize {
+        count_rank(&self.deck, rank)
+    }
+
+    fn dealt(&self) -> usize {
+        self.size as usize * 52 - self.deck.len()
+    }
+
+    fn reshuffle(&mut self) {
+        self.loading.extend(self.held.drain(..).flatten());
+        self.load();
     }
 }
 
@@ -67,14 +399,246 @@ mod shoe_tests {
     #[test]
     fn standard_shoe_creation() {
         let shoe = StandardShoe::new(8);
-        assert_eq!(shoe.deck.iter().filter(|x| **x == 10).count(), 128);
-        assert_eq!(shoe.deck.iter().filter(|x| **x == 3).count(), 32);
+        assert_eq!(shoe.deck.iter().filter(|x| x.value() == 10).count(), 128);
+        assert_eq!(
+            shoe.deck.iter().filter(|x| x.rank == Rank::King).count(),
+            32
+        );
+        assert_eq!(shoe.deck.iter().filter(|x| x.value() == 3).count(), 32);
+        assert_eq!(
+            shoe.deck.iter().filter(|x| x.suit == Suit::Hearts).count(),
+            104
+        );
+    }
+
+    #[test]
+    fn standard_shoe_penetration() {
+        let mut shoe = StandardShoe::with_penetration(1, Penetration::Fraction(0.5));
+        assert!(shoe.reshuffled());
+
+        (0..25).for_each(|_| {
+            shoe.deal().unwrap();
+        });
+        shoe.new_round(vec![]);
+        assert!(!shoe.reshuffled());
+        assert_eq!(shoe.deck.len(), 27);
+
+        shoe.deal().unwrap();
+        shoe.new_round(vec![]);
+        assert!(shoe.reshuffled());
+        assert_eq!(shoe.deck.len(), 52);
+    }
+
+    #[test]
+    fn standard_shoe_cut_card_position() {
+        let mut shoe = StandardShoe::with_penetration(2, Penetration::Cards(60));
+        (0..60).for_each(|_| {
+            shoe.deal().unwrap();
+        });
+        shoe.new_round(vec![]);
+        assert!(shoe.reshuffled());
+        assert_eq!(shoe.deck.len(), 104);
+    }
+
+    #[test]
+    fn standard_shoe_seed() {
+        let mut shoe = StandardShoe::with_seed(1, Penetration::Fraction(0.5), 21);
+        let mut replay = StandardShoe::with_seed(1, Penetration::Fraction(0.5), shoe.seed());
+
+        (0..100).for_each(|_| {
+            shoe.new_round(vec![]);
+            replay.new_round(vec![]);
+            assert_eq!(shoe.deal(), replay.deal());
+        });
+    }
+
+    #[test]
+    fn standard_shoe_seed_sequence() {
+        let mut shoe = StandardShoe::with_seed(1, Penetration::Fraction(0.75), 0);
+        let cards: Vec<String> = (0..5).map(|_| shoe.deal().unwrap().to_string()).collect();
+        assert_eq!(cards, ["9♥", "J♥", "Q♠", "K♠", "10♣"]);
+    }
+
+    #[test]
+    fn standard_shoe_refills_from_discards() {
+        let mut shoe = StandardShoe::with_seed(1, Penetration::Cards(52), 3);
+        let discards: Vec<Card> = (0..40).map(|_| shoe.deal().unwrap()).collect();
+        shoe.new_round(discards.clone());
+        assert!(!shoe.reshuffled());
+
+        let mut table: Vec<Card> = (0..12).map(|_| shoe.deal().unwrap()).collect();
+        table.push(shoe.deal().unwrap());
+        assert!(shoe.reshuffled());
+        assert_eq!(shoe.remaining(), 39);
+        assert!(discards.contains(&table[12]));
+
+        shoe.new_round(table);
+        shoe.reshuffle();
+        assert_eq!(shoe.remaining(), 52);
+        let mut cards: Vec<Card> = (0..52).map(|_| shoe.deal().unwrap()).collect();
+        cards.sort_by_key(|card| (card.suit as u8, card.rank));
+        cards.dedup();
+        assert_eq!(cards.len(), 52);
+    }
+
+    #[test]
+    fn custom_shoe_deals_in_order() {
+        let mut shoe: CustomShoe = "A 10 8h 8D 6".parse().unwrap();
+        assert_eq!(shoe.deal(), Ok(Card::new(Rank::Ace, Suit::Spades)));
+        assert_eq!(shoe.deal(), Ok(Card::new(Rank::Ten, Suit::Spades)));
+        assert_eq!(shoe.deal(), Ok(Card::new(Rank::Eight, Suit::Hearts)));
+        assert_eq!(shoe.deal(), Ok(Card::new(Rank::Eight, Suit::Diamonds)));
+        assert_eq!(shoe.remaining(), 1);
+        assert_eq!(shoe.dealt(), 4);
+    }
+
+    #[test]
+    fn custom_shoe_parse_error() {
+        assert_eq!(
+            "A 11 8".parse::<CustomShoe>().err(),
+            Some(ParseCardError::InvalidRank)
+        );
+        assert_eq!(
+            "A 8X".parse::<CustomShoe>().err(),
+            Some(ParseCardError::InvalidSuit)
+        );
+    }
+
+    #[test]
+    fn custom_shoe_fallback() {
+        let mut shoe = CustomShoe::with_fallback(
+            vec![Card::new(Rank::King, Suit::Clubs)],
+            CustomShoe::new(vec![Card::new(Rank::Two, Suit::Hearts)]),
+        );
+        assert_eq!(shoe.deal(), Ok(Card::new(Rank::King, Suit::Clubs)));
+        assert_eq!(shoe.deal(), Ok(Card::new(Rank::Two, Suit::Hearts)));
+    }
+
+    #[test]
+    fn custom_shoe_no_cards() {
+        let mut shoe = CustomShoe::new(vec![]);
+        assert_eq!(shoe.deal(), Err(ShoeError::Exhausted));
+    }
+
+    #[test]
+    fn continuous_shuffle_shoe_seed_sequence() {
+        let mut shoe = ContinuousShuffleShoe::with_seed(1, 0, 1, 0);
+        let discards: Vec<Card> = (0..10).map(|_| shoe.deal().unwrap()).collect();
+        shoe.new_round(discards.clone());
+        let positions: Vec<usize> = discards
+            .iter()
+            .map(|card| shoe.deck.iter().position(|x| x == card).unwrap())
+            .collect();
+        assert_eq!(positions, vec![30, 47, 50, 16, 35, 13, 28, 10, 4, 22]);
+    }
+
+    #[test]
+    fn continuous_shuffle_shoe_delay() {
+        let mut shoe = ContinuousShuffleShoe::with_seed(1, 1, 1, 7);
+        let discards: Vec<Card> = (0..4).map(|_| shoe.deal().unwrap()).collect();
+
+        shoe.new_round(discards.clone());
+        assert_eq!(shoe.deck.len(), 48);
+
+        shoe.new_round(vec![]);
+        assert_eq!(shoe.deck.len(), 52);
+        assert!(discards.iter().all(|card| shoe.deck.contains(card)));
+    }
+
+    #[test]
+    fn continuous_shuffle_shoe_batch_size() {
+        let mut shoe = ContinuousShuffleShoe::with_seed(1, 0, 6, 7);
+        let discards: Vec<Card> = (0..4).map(|_| shoe.deal().unwrap()).collect();
+        shoe.new_round(discards);
+        assert_eq!(shoe.deck.len(), 48);
+
+        let discards: Vec<Card> = (0..2).map(|_| shoe.deal().unwrap()).collect();
+        shoe.new_round(discards);
+        assert_eq!(shoe.deck.len(), 52);
+    }
+
+    #[test]
+    fn continuous_shuffle_shoe_never_runs_out() {
+        let mut shoe = ContinuousShuffleShoe::with_seed(1, 1, 1, 7);
+        let discards: Vec<Card> = (0..52).map(|_| shoe.deal().unwrap()).collect();
+        shoe.new_round(discards);
+        shoe.deal().unwrap();
+        assert_eq!(shoe.deck.len(), 51);
+    }
+
+    #[test]
+    fn standard_shoe_burn_cards() {
+        let mut shoe =
+            StandardShoe::with_penetration(1, Penetration::Fraction(0.5)).with_burn_cards(1);
+        assert_eq!(shoe.remaining(), 51);
+        assert_eq!(shoe.dealt(), 1);
+
+        (0..30).for_each(|_| {
+            shoe.deal().unwrap();
+        });
+        shoe.new_round(vec![]);
+        assert!(shoe.reshuffled());
+        assert_eq!(shoe.remaining(), 51);
+    }
+
+    #[test]
+    fn shoe_composition() {
+        let mut shoe: Box<dyn Shoe> = StandardShoe::new(2);
+        assert_eq!(shoe.remaining_by_rank(Rank::Ace), 8);
+        assert_eq!(shoe.decks_remaining(), 2.0);
+
+        let card = shoe.deal().unwrap();
+        assert_eq!(shoe.remaining_by_rank(card.rank), 7);
+        assert_eq!(shoe.remaining(), 103);
+        assert_eq!(shoe.dealt(), 1);
+    }
+
+    #[test]
+    fn custom_shoe_composition_with_fallback() {
+        let shoe = CustomShoe::with_fallback(
+            vec![Card::new(Rank::Ace, Suit::Clubs)],
+            CustomShoe::new(vec![Card::new(Rank::Ace, Suit::Hearts)]),
+        );
+        assert_eq!(shoe.remaining(), 2);
+        assert_eq!(shoe.remaining_by_rank(Rank::Ace), 2);
+        assert_eq!(shoe.remaining_by_rank(Rank::King), 0);
+    }
+
+    #[test]
+    fn standard_shoe_shuffle_uses_discards() {
+        let mut shoe = StandardShoe::with_penetration(1, Penetration::Cards(4))
+            .with_shuffle(vec![ShuffleModel::StripCut { strips: 1 }]);
+        let discards: Vec<Card> = (0..4).map(|_| shoe.deal().unwrap()).collect();
+        let behind_cut_card = shoe.deck.clone();
+
+        shoe.new_round(discards.clone());
+        assert!(shoe.reshuffled());
+        assert_eq!(shoe.deck[..4], discards[..]);
+        assert_eq!(shoe.deck[4..], behind_cut_card[..]);
+    }
+
+    #[test]
+    fn standard_shoe_shuffle_replaces_missing_cards() {
+        let mut shoe = StandardShoe::with_penetration(1, Penetration::Cards(4))
+            .with_shuffle(vec![ShuffleModel::Riffle { passes: 7 }]);
+        let mut discards: Vec<Card> = (0..4).map(|_| shoe.deal().unwrap()).collect();
+        discards.push(discards[0]);
+        discards.pop();
+        discards.pop();
+
+        shoe.new_round(discards);
+        let mut cards = shoe.deck.clone();
+        cards.sort_by_key(|card| (card.suit as u8, card.rank));
+        assert_eq!(cards, new_deck(1));
     }
 
     #[test]
     fn standard_shoe_no_cards() {
         let mut shoe = StandardShoe::new(1);
         shoe.deck.clear();
-        shoe.deal();
+        assert_eq!(shoe.deal(), Err(ShoeError::Exhausted));
+
+        shoe.new_round(vec![]);
+        assert!(shoe.deal().is_ok());
     }
 }
@@ -0,0 +1,171 @@
+use crate::Card;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Cards are ordered from the bottom of the pile to the top, the same way the shoe deals them.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum ShuffleModel {
+    /// Every order of the cards is equally likely.
+    Uniform,
+    /// Gilbert–Shannon–Reeds riffles of the whole pile.
+    Riffle { passes: usize },
+    /// Packets of roughly equal size are pulled off the top and stacked in reverse order.
+    StripCut { strips: usize },
+    /// The pile is split in two and `grab` cards from the top of each half are riffled together
+    /// `riffles` times before being stacked, until both halves are used up.
+    Zone { grab: usize, riffles: usize },
+}
+
+impl ShuffleModel {
+    pub(crate) fn apply(&self, cards: Vec<Card>, rng: &mut impl Rng) -> Vec<Card> {
+        match *self {
+            ShuffleModel::Uniform => {
+                let mut cards = cards;
+                cards.shuffle(rng);
+                cards
+            }
+            ShuffleModel::Riffle { passes } => {
+                (0..passes).fold(cards, |cards, _| riffle(cards, rng))
+            }
+            ShuffleModel::StripCut { strips } => strip_cut(cards, strips, rng),
+            ShuffleModel::Zone { grab, riffles } => zone(cards, grab, riffles, rng),
+        }
+    }
+}
+
+/// A random index below `len`. It samples a `u32` like `SliceRandom::shuffle` does, since
+/// `gen_range` over `usize` draws differently on 32 and 64-bit targets and seeded shoes have to
+/// deal the same cards everywhere.
+pub(crate) fn random_index(rng: &mut impl Rng, len: usize) -> usize {
+    rng.gen_range(0..len as u32) as usize
+}
+
+fn riffle(cards: Vec<Card>, rng: &mut impl Rng) -> Vec<Card> {
+    let n = cards.len();
+    let cut = (0..n).filter(|_| rng.gen_bool(0.5)).count();
+
+    let (mut left, mut right) = (0, cut);
+    let mut riffled = Vec::with_capacity(n);
+    while left < cut || right < n {
+        if random_index(rng, n - right + cut - left) < cut - left {
+            riffled.push(cards[left]);
+            left += 1;
+        } else {
+            riffled.push(cards[right]);
+            right += 1;
+        }
+    }
+    riffled
+}
+
+fn strip_cut(mut cards: Vec<Card>, strips: usize, rng: &mut impl Rng) -> Vec<Card> {
+    let mut stripped = Vec::with_capacity(cards.len());
+    for strips_left in (1..=strips.max(1)).rev() {
+        let size = if strips_left == 1 {
+            cards.len()
+        } else {
+            let average = cards.len() / strips_left;
+            average - average / 4 + random_index(rng, average / 4 * 2 + 1)
+        };
+        stripped.extend(cards.split_off(cards.len() - size));
+    }
+    stripped
+}
+
+fn zone(mut cards: Vec<Card>, grab: usize, riffles: usize, rng: &mut impl Rng) -> Vec<Card> {
+    let grab = grab.max(1);
+    let mut right = cards.split_off(cards.len() / 2);
+    let mut left = cards;
+
+    let mut zoned = Vec::with_capacity(left.len() + right.len());
+    while !left.is_empty() || !right.is_empty() {
+        let mut packet = left.split_off(left.len().saturating_sub(grab));
+        packet.extend(right.split_off(right.len().saturating_sub(grab)));
+        zoned.extend((0..riffles).fold(packet, |packet, _| riffle(packet, rng)));
+    }
+    zoned
+}
+
+#[cfg(test)]
+mod shuffle_tests {
+    use super::*;
+    use crate::{Rank, Suit};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn ordered_cards() -> Vec<Card> {
+        Suit::ALL
+            .iter()
+            .flat_map(|&suit| Rank::ALL.map(|rank| Card::new(rank, suit)))
+            .collect()
+    }
+
+    fn is_permutation(cards: &[Card]) -> bool {
+        let mut sorted = cards.to_vec();
+        sorted.sort_by_key(|card| (card.suit as u8, card.rank));
+        sorted == ordered_cards()
+    }
+
+    #[test]
+    fn shuffle_models_keep_every_card() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        for model in [
+            ShuffleModel::Uniform,
+            ShuffleModel::Riffle { passes: 3 },
+            ShuffleModel::StripCut { strips: 5 },
+            ShuffleModel::Zone {
+                grab: 10,
+                riffles: 2,
+            },
+        ] {
+            assert!(is_permutation(&model.apply(ordered_cards(), &mut rng)));
+        }
+    }
+
+    #[test]
+    fn riffle_keeps_packet_order() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let cards = ordered_cards();
+        let riffled = ShuffleModel::Riffle { passes: 1 }.apply(cards.clone(), &mut rng);
+
+        // a single riffle interleaves two packets, so the cards form at most two rising sequences
+        let positions: Vec<usize> = cards
+            .iter()
+            .map(|card| riffled.iter().position(|x| x == card).unwrap())
+            .collect();
+        let descents = positions.windows(2).filter(|x| x[0] > x[1]).count();
+        assert!(descents <= 1);
+    }
+
+    #[test]
+    fn strip_cut_reverses_packets() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let cards = ordered_cards();
+        let stripped = ShuffleModel::StripCut { strips: 1 }.apply(cards.clone(), &mut rng);
+        assert_eq!(stripped, cards);
+
+        let stripped = ShuffleModel::StripCut { strips: 4 }.apply(cards.clone(), &mut rng);
+        let bottom = stripped.iter().position(|x| *x == cards[0]).unwrap();
+        assert!(bottom > 0);
+        assert_eq!(stripped[bottom..], cards[..52 - bottom]);
+    }
+
+    #[test]
+    fn seeded_shuffles_are_fixed() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let cards: Vec<String> = ShuffleModel::Riffle { passes: 2 }
+            .apply(ordered_cards(), &mut rng)
+            .iter()
+            .rev()
+            .take(5)
+            .map(|card| card.to_string())
+            .collect();
+        assert_eq!(cards, ["K♠", "9♦", "K♥", "Q♠", "10♣"]);
+
+        let stripped = ShuffleModel::StripCut { strips: 4 }.apply(ordered_cards(), &mut rng);
+        assert_eq!(
+            stripped.iter().position(|x| *x == ordered_cards()[0]),
+            Some(38)
+        );
+    }
+}